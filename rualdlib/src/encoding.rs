@@ -0,0 +1,115 @@
+//! Lossless representation of paths which are not valid UTF-8.
+//!
+//! Such paths are written in the aliases file with their invalid bytes
+//! as `\xHH` and backslashes doubled, the entry being marked `escaped`:
+//! ```toml
+//! [aliases]
+//! legacy = { path = '/data/caf\xe9', escaped = true }
+//! ```
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+/// Escape the bytes of path, UTF-8 parts are kept as is
+pub(crate) fn escape_bytes(bytes: &[u8]) -> String {
+    let mut res = String::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        res.push_str(&escape_str(chunk.valid()));
+        for byte in chunk.invalid() {
+            res.push_str(&format!("\\x{:02x}", byte));
+        }
+    }
+    res
+}
+
+/// Escape a valid UTF-8 path so it can be joined with escaped paths
+pub(crate) fn escape_str(path: &str) -> Cow<'_, str> {
+    if path.contains('\\') {
+        Cow::Owned(path.replace('\\', "\\\\"))
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+/// Get back the bytes of an escaped path, unknown escapes are kept as is
+pub(crate) fn unescape(path: &str) -> Vec<u8> {
+    let bytes = path.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            if bytes.get(i + 1) == Some(&b'\\') {
+                res.push(b'\\');
+                i += 2;
+                continue;
+            }
+            if bytes.get(i + 1) == Some(&b'x') {
+                let byte = path
+                    .get(i + 2..i + 4)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let Some(byte) = byte {
+                    res.push(byte);
+                    i += 4;
+                    continue;
+                }
+            }
+        }
+        res.push(bytes[i]);
+        i += 1;
+    }
+    res
+}
+
+/// Get the bytes of path
+#[cfg(unix)]
+pub(crate) fn path_to_bytes(path: &Path) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Cow::Borrowed(path.as_os_str().as_bytes())
+}
+
+/// Get the bytes of path, non unicode parts are replaced
+#[cfg(not(unix))]
+pub(crate) fn path_to_bytes(path: &Path) -> Cow<'_, [u8]> {
+    match path.to_string_lossy() {
+        Cow::Borrowed(path) => Cow::Borrowed(path.as_bytes()),
+        Cow::Owned(path) => Cow::Owned(path.into_bytes()),
+    }
+}
+
+/// Build a path from its bytes
+#[cfg(unix)]
+pub(crate) fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+/// Build a path from its bytes, invalid UTF-8 sequences are replaced
+#[cfg(not(unix))]
+pub(crate) fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let bytes = b"/data/caf\xe9/back\\slash/\xff\xfe";
+        let escaped = escape_bytes(bytes);
+        assert_eq!(escaped, "/data/caf\\xe9/back\\\\slash/\\xff\\xfe");
+        assert_eq!(unescape(&escaped), bytes.to_vec());
+    }
+
+    #[test]
+    fn unknown_escapes() {
+        assert_eq!(unescape("/a\\b/\\xzz/\\x4"), b"/a\\b/\\xzz/\\x4".to_vec());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn path_bytes() {
+        let path = path_from_bytes(b"/data/caf\xe9".to_vec());
+        assert!(path.to_str().is_none());
+        assert_eq!(path_to_bytes(&path).as_ref(), b"/data/caf\xe9");
+    }
+}
@@ -0,0 +1,396 @@
+//! Alias entries of the `[aliases]` table
+use crate::encoding;
+use chrono::Local;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    convert::TryFrom,
+    path::{Path, PathBuf},
+};
+use toml::value::Datetime;
+
+/// Path pointed by an alias with its optional metadata.
+///
+/// An entry without metadata is written as a plain string, as in
+/// previous versions of rualdi. A list of candidate paths can be given
+/// for paths differing per machine, the first existing one is used:
+/// ```toml
+/// [aliases]
+/// workdir = "~/workdir"
+/// api = { path = "~/src/api", description = "REST API", tags = ["work"] }
+/// x = ["~/src/x", "/work/x", "/mnt/data/x"]
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(try_from = "EntryRepr", into = "EntryRepr")]
+pub struct AliasEntry {
+    pub path: String,
+    /// Candidate paths tried in order when `path` does not exist
+    pub alternatives: Vec<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub created: Option<Datetime>,
+    pub updated: Option<Datetime>,
+    /// Whether path is not valid UTF-8 and is stored escaped
+    pub escaped: bool,
+}
+
+impl AliasEntry {
+    /// Create an entry without metadata pointing on `path`
+    pub fn new<S: Into<String>>(path: S) -> Self {
+        AliasEntry {
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Create an entry without metadata pointing on `path`, a path which
+    /// is not valid UTF-8 is stored escaped. `$` is doubled so that the
+    /// path is not taken for environment variables when resolved.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref();
+        match path.to_str() {
+            Some(path) => AliasEntry::new(path.replace('$', "$$")),
+            None => AliasEntry {
+                escaped: true,
+                ..AliasEntry::new(
+                    encoding::escape_bytes(&encoding::path_to_bytes(path)).replace('$', "$$"),
+                )
+            },
+        }
+    }
+
+    /// Get the candidate paths of the entry in order, `path` first
+    pub fn paths(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.path).chain(self.alternatives.iter())
+    }
+
+    /// Get the path of the entry, with its non unicode sequences replaced
+    pub fn path_lossy(&self) -> Cow<'_, str> {
+        if self.escaped {
+            Cow::Owned(String::from_utf8_lossy(&encoding::unescape(&self.path)).into_owned())
+        } else {
+            Cow::Borrowed(&self.path)
+        }
+    }
+
+    /// Get the path of the entry as stored, not expanded
+    pub fn to_path_buf(&self) -> PathBuf {
+        if self.escaped {
+            encoding::path_from_bytes(encoding::unescape(&self.path))
+        } else {
+            PathBuf::from(&self.path)
+        }
+    }
+
+    /// Get the candidate paths of the entry escaped, valid UTF-8 paths get
+    /// their backslashes escaped so they can be joined with escaped paths
+    pub(crate) fn escaped_paths(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        self.paths().map(move |path| {
+            if self.escaped {
+                Cow::Borrowed(path.as_str())
+            } else {
+                encoding::escape_str(path)
+            }
+        })
+    }
+
+    /// Point the entry on the paths of `other`, metadata are kept
+    pub(crate) fn set_path(&mut self, other: AliasEntry) -> AliasEntry {
+        let old = AliasEntry {
+            path: std::mem::replace(&mut self.path, other.path),
+            alternatives: std::mem::replace(&mut self.alternatives, other.alternatives),
+            escaped: self.escaped,
+            ..Default::default()
+        };
+        self.escaped = other.escaped;
+        old
+    }
+
+    /// Set the description of the entry
+    pub fn with_description<S: Into<String>>(mut self, description: Option<S>) -> Self {
+        self.description = description.map(Into::into);
+        self
+    }
+
+    /// Set the tags of the entry, duplicated tags are dropped
+    pub fn with_tags<S: Into<String>>(mut self, tags: Vec<S>) -> Self {
+        self.tags = Vec::new();
+        for tag in tags.into_iter().map(Into::into) {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+        self
+    }
+
+    /// Whether the entry carries every tag of `tags`
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter().all(|tag| self.tags.contains(tag))
+    }
+
+    /// Whether the entry only holds a path and can be written as a string
+    pub fn is_plain(&self) -> bool {
+        self.description.is_none()
+            && self.tags.is_empty()
+            && self.created.is_none()
+            && self.updated.is_none()
+            && !self.escaped
+    }
+
+    /// Record the entry as modified now, entries carrying metadata also
+    /// get their creation date recorded if missing
+    pub(crate) fn touch(&mut self) {
+        if self.description.is_none() && self.tags.is_empty() {
+            return;
+        }
+        let now = now();
+        if self.created.is_none() {
+            self.created = now.clone();
+        }
+        self.updated = now;
+    }
+}
+
+impl From<&str> for AliasEntry {
+    fn from(path: &str) -> Self {
+        AliasEntry::new(path)
+    }
+}
+
+impl From<String> for AliasEntry {
+    fn from(path: String) -> Self {
+        AliasEntry::new(path)
+    }
+}
+
+/// Current local time as a TOML datetime
+fn now() -> Option<Datetime> {
+    Local::now()
+        .format("%Y-%m-%dT%H:%M:%S%:z")
+        .to_string()
+        .parse()
+        .ok()
+}
+
+/// On-disk representation of the paths of an entry, either a path
+/// or a list of candidate paths
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum PathRepr {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl PathRepr {
+    fn new(path: String, alternatives: Vec<String>) -> Self {
+        if alternatives.is_empty() {
+            PathRepr::One(path)
+        } else {
+            PathRepr::Many(std::iter::once(path).chain(alternatives).collect())
+        }
+    }
+
+    /// Split into the first path and the alternatives,
+    /// raise an error if there is no candidate
+    fn split(self) -> Result<(String, Vec<String>), &'static str> {
+        match self {
+            PathRepr::One(path) => Ok((path, Vec::new())),
+            PathRepr::Many(paths) if paths.is_empty() => Err("list of candidate paths is empty"),
+            PathRepr::Many(mut paths) => {
+                let path = paths.remove(0);
+                Ok((path, paths))
+            }
+        }
+    }
+}
+
+/// On-disk representation of an entry, either paths or a table
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum EntryRepr {
+    Path(PathRepr),
+    Table {
+        path: PathRepr,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        created: Option<Datetime>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        updated: Option<Datetime>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        escaped: bool,
+    },
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl TryFrom<EntryRepr> for AliasEntry {
+    type Error = &'static str;
+
+    fn try_from(repr: EntryRepr) -> Result<Self, Self::Error> {
+        Ok(match repr {
+            EntryRepr::Path(path) => {
+                let (path, alternatives) = path.split()?;
+                AliasEntry {
+                    alternatives,
+                    ..AliasEntry::new(path)
+                }
+            }
+            EntryRepr::Table {
+                path,
+                description,
+                tags,
+                created,
+                updated,
+                escaped,
+            } => {
+                let (path, alternatives) = path.split()?;
+                AliasEntry {
+                    path,
+                    alternatives,
+                    description,
+                    tags,
+                    created,
+                    updated,
+                    escaped,
+                }
+            }
+        })
+    }
+}
+
+impl From<AliasEntry> for EntryRepr {
+    fn from(entry: AliasEntry) -> Self {
+        let plain = entry.is_plain();
+        let path = PathRepr::new(entry.path, entry.alternatives);
+        if plain {
+            EntryRepr::Path(path)
+        } else {
+            EntryRepr::Table {
+                path,
+                description: entry.description,
+                tags: entry.tags,
+                created: entry.created,
+                updated: entry.updated,
+                escaped: entry.escaped,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Table {
+        aliases: BTreeMap<String, AliasEntry>,
+    }
+
+    #[test]
+    fn plain_path() {
+        let table: Table = toml::from_str("[aliases]\ntest = '/test'\n").unwrap();
+        assert_eq!(table.aliases["test"], AliasEntry::new("/test"));
+        assert_eq!(
+            toml::to_string(&table).unwrap(),
+            "[aliases]\ntest = \"/test\"\n"
+        );
+    }
+
+    #[test]
+    fn table() {
+        let table: Table = toml::from_str(
+            r#"[aliases]
+api = { path = "~/src/api", description = "REST API", tags = ["work"], created = 2021-08-01T10:00:00Z }
+"#,
+        )
+        .unwrap();
+        let entry = &table.aliases["api"];
+        assert_eq!(entry.path, "~/src/api");
+        assert_eq!(entry.description, Some("REST API".into()));
+        assert_eq!(entry.tags, vec![String::from("work")]);
+        assert_eq!(
+            entry.created.as_ref().map(ToString::to_string),
+            Some("2021-08-01T10:00:00Z".into())
+        );
+        assert_eq!(entry.updated, None);
+
+        let round_trip: Table = toml::from_str(&toml::to_string(&table).unwrap()).unwrap();
+        assert_eq!(round_trip, table);
+    }
+
+    #[test]
+    fn candidates() {
+        let content = r#"[aliases]
+x = ["~/src/x", "/work/x"]
+
+[aliases.y]
+path = ["~/src/y", "/work/y"]
+description = "y"
+"#;
+        let table: Table = toml::from_str(content).unwrap();
+        let x = &table.aliases["x"];
+        assert_eq!(x.path, "~/src/x");
+        assert_eq!(x.alternatives, vec![String::from("/work/x")]);
+        assert!(x.is_plain());
+        assert_eq!(table.aliases["y"].paths().count(), 2);
+        assert_eq!(toml::to_string(&table).unwrap(), content);
+    }
+
+    #[test]
+    fn no_candidate() {
+        let res = toml::from_str::<Table>("[aliases]\nx = []\n");
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains("list of candidate paths is empty"));
+        assert!(toml::from_str::<Table>("[aliases.x]\npath = []\n").is_err());
+    }
+
+    #[test]
+    fn tags() {
+        let entry = AliasEntry::new("/test").with_tags(vec!["work", "rust", "work"]);
+        assert_eq!(entry.tags, vec![String::from("work"), String::from("rust")]);
+        assert!(entry.has_tags(&[]));
+        assert!(entry.has_tags(&["rust".into(), "work".into()]));
+        assert!(!entry.has_tags(&["work".into(), "home".into()]));
+    }
+
+    #[test]
+    fn touch() {
+        let mut plain = AliasEntry::new("/test");
+        plain.touch();
+        assert!(plain.is_plain());
+
+        let mut described = AliasEntry::new("/test").with_description(Some("test"));
+        described.touch();
+        assert!(described.created.is_some());
+        assert_eq!(described.created, described.updated);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn escaped() {
+        let path = crate::encoding::path_from_bytes(b"/data/caf\xe9".to_vec());
+        let entry = AliasEntry::from_path(&path);
+        assert!(entry.escaped);
+        assert_eq!(entry.path_lossy(), "/data/caf\u{fffd}");
+        assert_eq!(entry.to_path_buf(), path);
+
+        let table = Table {
+            aliases: vec![("legacy".to_string(), entry)].into_iter().collect(),
+        };
+        let content = toml::to_string(&table).unwrap();
+        assert_eq!(
+            content,
+            "[aliases.legacy]\npath = \"/data/caf\\\\xe9\"\nescaped = true\n"
+        );
+        let round_trip: Table = toml::from_str(&content).unwrap();
+        assert_eq!(round_trip, table);
+    }
+}
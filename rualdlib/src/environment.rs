@@ -0,0 +1,193 @@
+//! Environment variable entries of the `[environment]` table
+use serde_derive::{Deserialize, Serialize};
+use std::{fmt, path::Path, str::FromStr};
+
+/// How the names of prefixed alias environment variables are cased
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EnvCase {
+    #[default]
+    Upper,
+    Lower,
+    Keep,
+}
+
+impl EnvCase {
+    pub fn apply(self, name: &str) -> String {
+        match self {
+            EnvCase::Upper => name.to_uppercase(),
+            EnvCase::Lower => name.to_lowercase(),
+            EnvCase::Keep => name.to_owned(),
+        }
+    }
+}
+
+impl FromStr for EnvCase {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "upper" => Ok(EnvCase::Upper),
+            "lower" => Ok(EnvCase::Lower),
+            "keep" => Ok(EnvCase::Keep),
+            _ => Err(format!("unknown case '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for EnvCase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let case = match self {
+            EnvCase::Upper => "upper",
+            EnvCase::Lower => "lower",
+            EnvCase::Keep => "keep",
+        };
+        write!(f, "{}", case)
+    }
+}
+
+/// Alias pointed by an environment variable, optionally followed by a
+/// subpath. Variables are exported with the configured prefix unless
+/// `prefix = false` is given, for names expected as is by other tools:
+/// ```toml
+/// [environment]
+/// API = "api"
+/// API_SRC = "api/src"
+/// API_ROOT = { alias = "api", prefix = false }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "EnvRepr", into = "EnvRepr")]
+pub struct EnvEntry {
+    /// Alias followed by an optional subpath, as `alias/sub`
+    pub target: String,
+    /// Whether the variable is exported with the configured prefix
+    pub prefixed: bool,
+}
+
+impl EnvEntry {
+    /// Create an entry exported with the configured prefix
+    pub fn new<S: Into<String>>(target: S) -> Self {
+        EnvEntry {
+            target: target.into(),
+            prefixed: true,
+        }
+    }
+
+    /// Set whether the variable is exported with the configured prefix
+    pub fn with_prefix(mut self, prefixed: bool) -> Self {
+        self.prefixed = prefixed;
+        self
+    }
+
+    /// Get the alias the variable points on
+    pub fn alias(&self) -> &str {
+        self.target.split('/').next().unwrap_or_default()
+    }
+
+    /// Get the path below the alias the variable points on, if any
+    pub fn subpath(&self) -> Option<&Path> {
+        self.target
+            .split_once('/')
+            .map(|(_, sub)| Path::new(sub))
+            .filter(|sub| !sub.as_os_str().is_empty())
+    }
+
+    /// Point the variable on `alias`, keeping its subpath
+    pub fn retarget(&mut self, alias: &str) {
+        self.target = match self.target.split_once('/') {
+            Some((_, sub)) => format!("{}/{}", alias, sub),
+            None => alias.to_owned(),
+        };
+    }
+}
+
+impl From<&str> for EnvEntry {
+    fn from(target: &str) -> Self {
+        EnvEntry::new(target)
+    }
+}
+
+impl From<String> for EnvEntry {
+    fn from(target: String) -> Self {
+        EnvEntry::new(target)
+    }
+}
+
+/// On-disk representation of an entry, a table is only
+/// needed for variables exported without prefix
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum EnvRepr {
+    Target(String),
+    Table {
+        alias: String,
+        #[serde(default = "default_prefix")]
+        prefix: bool,
+    },
+}
+
+fn default_prefix() -> bool {
+    true
+}
+
+impl From<EnvRepr> for EnvEntry {
+    fn from(repr: EnvRepr) -> Self {
+        match repr {
+            EnvRepr::Target(target) => EnvEntry::new(target),
+            EnvRepr::Table { alias, prefix } => EnvEntry::new(alias).with_prefix(prefix),
+        }
+    }
+}
+
+impl From<EnvEntry> for EnvRepr {
+    fn from(entry: EnvEntry) -> Self {
+        if entry.prefixed {
+            EnvRepr::Target(entry.target)
+        } else {
+            EnvRepr::Table {
+                alias: entry.target,
+                prefix: false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Table {
+        environment: BTreeMap<String, EnvEntry>,
+    }
+
+    #[test]
+    fn round_trip() {
+        let content = r#"[environment]
+API = "api"
+API_SRC = "api/src"
+
+[environment.API_ROOT]
+alias = "api"
+prefix = false
+"#;
+        let table: Table = toml::from_str(content).unwrap();
+        assert_eq!(table.environment["API"], EnvEntry::new("api"));
+        assert!(!table.environment["API_ROOT"].prefixed);
+        assert_eq!(
+            toml::Value::try_from(&table).unwrap(),
+            toml::from_str::<toml::Value>(content).unwrap()
+        );
+    }
+
+    #[test]
+    fn target() {
+        let mut entry = EnvEntry::new("api/src/handlers");
+        assert_eq!(entry.alias(), "api");
+        assert_eq!(entry.subpath(), Some(Path::new("src/handlers")));
+        entry.retarget("rest");
+        assert_eq!(entry.target, "rest/src/handlers");
+        assert_eq!(EnvEntry::new("api").subpath(), None);
+        assert_eq!(EnvEntry::new("api/").subpath(), None);
+    }
+}
@@ -0,0 +1,100 @@
+//! Errors raised while reading or modifying rualdi aliases
+use std::{error::Error as StdError, io, path::PathBuf};
+use thiserror::Error;
+
+/// Result type returned by rualdlib
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Kinds of failure raised by [`Aliases`](crate::Aliases), allowing callers
+/// to branch on the failure instead of matching error messages
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// An alias with the same name is already defined
+    #[error("alias '{0}' already exists")]
+    AliasExists(String),
+    /// No alias with this name is defined
+    #[error("alias '{0}' not exists")]
+    AliasNotFound(String),
+    /// The path of the alias references an alias which is not defined
+    #[error("alias '{alias}' references alias '{reference}' which not exists")]
+    BrokenReference { alias: String, reference: String },
+    /// Aliases reference each other in a loop
+    #[error("cyclic alias references: {}", .0.join(" -> "))]
+    AliasCycle(Vec<String>),
+    /// Several aliases match the name
+    #[error("alias '{name}' is ambiguous, candidates are: {}", .candidates.join(", "))]
+    AmbiguousAlias {
+        name: String,
+        candidates: Vec<String>,
+    },
+    /// The path of the alias uses an environment variable which is not set
+    #[error("environment variable '{var}' used by alias '{alias}' is not set")]
+    UnsetVariable {
+        alias: String,
+        var: String,
+        #[source]
+        source: std::env::VarError,
+    },
+    /// The environment variable is already linked to an alias
+    #[error("environment variable '{var}' for alias '{alias}' already exists")]
+    EnvVarTaken { alias: String, var: String },
+    /// The alias is linked to several environment variables where one is expected
+    #[error("alias '{0}' has several environment variables")]
+    SeveralEnvVars(String),
+    /// The alias is not linked to any environment variable
+    #[error("no such environment variable for alias '{0}'")]
+    EnvVarNotFound(String),
+    /// The alias or variable name cannot be used
+    #[error("invalid name '{name}': {reason}")]
+    InvalidName { name: String, reason: String },
+    /// The aliases file was written by a newer version of rualdi
+    #[error(
+        "alias file '{}' has version {found} but this rualdi only supports up to version {supported}, please upgrade rualdi",
+        path.display()
+    )]
+    UnsupportedVersion {
+        path: PathBuf,
+        found: i64,
+        supported: u32,
+    },
+    /// Reading or writing the aliases file failed
+    #[error("{context}")]
+    Io {
+        context: String,
+        #[source]
+        source: io::Error,
+    },
+    /// The aliases file is not valid TOML or does not match the expected layout
+    #[error("could not parse alias file: '{}'", path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: Box<dyn StdError + Send + Sync>,
+    },
+    /// The aliases could not be encoded in TOML
+    #[error("fail to encode aliases in toml")]
+    Encode(#[from] toml::ser::Error),
+}
+
+impl Error {
+    /// Build a closure wrapping an `io::Error` with a context message
+    pub(crate) fn io<C: Into<String>>(context: C) -> impl FnOnce(io::Error) -> Self {
+        move |source| Error::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    /// Build a closure wrapping a parsing error of the file at `path`
+    pub(crate) fn parse<P, E>(path: P) -> impl FnOnce(E) -> Self
+    where
+        P: Into<PathBuf>,
+        E: StdError + Send + Sync + 'static,
+    {
+        move |source| Error::Parse {
+            path: path.into(),
+            source: Box::new(source),
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Alias sections only applying on some machines.
+//!
+//! A `[hosts.<name>]` section applies when its `hostname` glob, the section
+//! name if missing, matches the hostname and every variable of `env` is set
+//! with a value matching its glob. A section only holding `env` predicates
+//! does not check the hostname.
+//! ```toml
+//! [hosts.buildbox.aliases]
+//! build = "/build"
+//!
+//! [hosts.laptops]
+//! hostname = "laptop-*"
+//! env = { WORK = "1" }
+//! [hosts.laptops.aliases]
+//! vpn = "~/work/vpn"
+//! ```
+use crate::{AliasEntry, Result};
+use glob::Pattern;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+};
+
+/// Aliases of a `[hosts.<name>]` section with the predicates selecting it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct HostSection {
+    /// Glob matched against the hostname
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// Environment variables which must be set, values are globs
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, AliasEntry>,
+}
+
+impl HostSection {
+    /// Whether section `name` applies on the machine called `hostname`
+    pub fn matches(&self, name: &str, hostname: Option<&str>) -> bool {
+        let pattern = match &self.hostname {
+            Some(pattern) => Some(pattern.as_str()),
+            None if self.env.is_empty() => Some(name),
+            None => None,
+        };
+        let host_matches = match pattern {
+            Some(pattern) => hostname.is_some_and(|hostname| glob_matches(pattern, hostname)),
+            None => true,
+        };
+        host_matches
+            && self.env.iter().all(|(var, pattern)| {
+                env::var(var).is_ok_and(|value| glob_matches(pattern, &value))
+            })
+    }
+}
+
+/// Sections the aliases applied over the base `[aliases]` come from
+#[derive(Debug, Default)]
+pub(crate) struct Origins {
+    /// Section of each alias coming from a host section
+    current: BTreeMap<String, String>,
+    /// Same as `current` when opened, to drop removed aliases on save
+    loaded: BTreeMap<String, String>,
+    /// Base aliases hidden by a host section, kept as is on save
+    shadowed: BTreeMap<String, AliasEntry>,
+}
+
+impl Origins {
+    /// Apply the sections matching `hostname` over `aliases`, in name
+    /// order so the last matching section wins
+    pub fn apply(
+        hosts: &BTreeMap<String, HostSection>,
+        hostname: Option<&str>,
+        aliases: &mut BTreeMap<String, AliasEntry>,
+    ) -> Self {
+        let mut origins = Origins::default();
+        for (name, section) in hosts {
+            if !section.matches(name, hostname) {
+                continue;
+            }
+            for (alias, entry) in &section.aliases {
+                if let Some(base) = aliases.insert(alias.to_owned(), entry.to_owned()) {
+                    if !origins.current.contains_key(alias) {
+                        origins.shadowed.insert(alias.to_owned(), base);
+                    }
+                }
+                origins.current.insert(alias.to_owned(), name.to_owned());
+            }
+        }
+        origins.loaded = origins.current.clone();
+        origins
+    }
+
+    /// Get the section `alias` comes from, `None` for base aliases
+    pub fn get(&self, alias: &str) -> Option<&str> {
+        self.current.get(alias).map(String::as_str)
+    }
+
+    /// Keep a renamed alias in its section
+    pub fn rename(&mut self, alias: &str, new_alias: &str) {
+        if let Some(name) = self.current.remove(alias) {
+            self.current.insert(new_alias.to_owned(), name);
+        }
+    }
+
+    /// Forget the section of a removed alias, returning the base
+    /// alias it was hiding
+    pub fn remove(&mut self, alias: &str) -> Option<AliasEntry> {
+        self.current.remove(alias)?;
+        self.shadowed.remove(alias)
+    }
+
+    /// Move the aliases of `updated`, the serialized aliases, coming from a
+    /// host section back to it and restore the base aliases they hide
+    pub fn split(&self, updated: &mut toml::Value) -> Result<()> {
+        let table = match updated.as_table_mut() {
+            Some(table) => table,
+            None => return Ok(()),
+        };
+
+        let mut moved: BTreeMap<&str, toml::value::Table> = BTreeMap::new();
+        let aliases = table
+            .entry("aliases")
+            .or_insert_with(|| toml::Value::Table(Default::default()));
+        if let Some(aliases) = aliases.as_table_mut() {
+            for (alias, name) in &self.current {
+                if let Some(entry) = aliases.remove(alias) {
+                    moved
+                        .entry(name)
+                        .or_default()
+                        .insert(alias.to_owned(), entry);
+                }
+            }
+            for (alias, entry) in &self.shadowed {
+                if !aliases.contains_key(alias) {
+                    aliases.insert(alias.to_owned(), toml::Value::try_from(entry)?);
+                }
+            }
+        }
+
+        let names: BTreeSet<_> = self.loaded.values().chain(self.current.values()).collect();
+        for name in names {
+            let section = table
+                .get_mut("hosts")
+                .and_then(|hosts| hosts.get_mut(name))
+                .and_then(toml::Value::as_table_mut);
+            let section = match section {
+                Some(section) => section,
+                None => continue,
+            };
+            let aliases = section
+                .entry("aliases")
+                .or_insert_with(|| toml::Value::Table(Default::default()));
+            if let Some(aliases) = aliases.as_table_mut() {
+                for (alias, loaded) in &self.loaded {
+                    if loaded == name {
+                        aliases.remove(alias);
+                    }
+                }
+                for (alias, entry) in moved.remove(name.as_str()).unwrap_or_default() {
+                    aliases.insert(alias, entry);
+                }
+                if aliases.is_empty() {
+                    section.remove("aliases");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Match `value` against a glob, an invalid glob must be equal to `value`
+fn glob_matches(pattern: &str, value: &str) -> bool {
+    match Pattern::new(pattern) {
+        Ok(pattern) => pattern.matches(value),
+        Err(_) => pattern == value,
+    }
+}
+
+/// Get the name of the machine
+#[cfg(unix)]
+pub fn hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    // SAFETY: the buffer is valid for its whole length, gethostname
+    // writes at most that many bytes
+    let res = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    if res != 0 {
+        return None;
+    }
+    let len = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    String::from_utf8(buffer[..len].to_vec()).ok()
+}
+
+/// Get the name of the machine
+#[cfg(not(unix))]
+pub fn hostname() -> Option<String> {
+    env::var("COMPUTERNAME").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hostname_glob() {
+        let section = HostSection {
+            hostname: Some("laptop-*".into()),
+            ..Default::default()
+        };
+        assert!(section.matches("laptops", Some("laptop-42")));
+        assert!(!section.matches("laptops", Some("buildbox")));
+        assert!(!section.matches("laptops", None));
+    }
+
+    #[test]
+    fn section_name() {
+        let section = HostSection::default();
+        assert!(section.matches("buildbox", Some("buildbox")));
+        assert!(!section.matches("buildbox", Some("laptop-42")));
+    }
+
+    #[test]
+    fn env_predicate() {
+        env::set_var("RUALDI_TEST_HOSTS", "work-1");
+        let mut section = HostSection::default();
+        section
+            .env
+            .insert("RUALDI_TEST_HOSTS".into(), "work-*".into());
+        assert!(section.matches("anywhere", Some("buildbox")));
+        section
+            .env
+            .insert("RUALDI_TEST_HOSTS_UNSET".into(), "*".into());
+        assert!(!section.matches("anywhere", Some("buildbox")));
+    }
+}
@@ -1,8 +1,24 @@
 //! Module to parse rad config file in TOML format
-use anyhow::{anyhow, Context, Result};
+mod encoding;
+mod entry;
+mod environment;
+mod error;
+mod hosts;
+mod visits;
+
+pub use entry::AliasEntry;
+pub use environment::{EnvCase, EnvEntry};
+pub use error::{Error, Result};
+pub use hosts::HostSection;
+pub use visits::{Visit, Visits};
+
+use chrono::{Local, TimeZone, Utc};
+use fs2::FileExt;
 use serde_derive::{Deserialize, Serialize};
-use shellexpand::{full, tilde, LookupError};
+use shellexpand::{env_with_context, full, full_with_context, LookupError};
+use tempfile::NamedTempFile;
 use terminal_size::terminal_size;
+use toml_edit::{Document, Item};
 
 use std::{
     borrow::Cow,
@@ -16,41 +32,165 @@ use std::{
 use indexmap::IndexMap;
 
 #[cfg(test)]
-use std::{fs::File, io::Write};
+use std::fs::File;
 
 use colored::*;
 use regex::{Captures, Regex};
 #[cfg(test)]
 use tempfile::{Builder, TempDir};
 
+/// Version of the aliases file layout written by this library. Files
+/// without a `version` key are considered to be at version 0.
+pub const CONFIG_VERSION: u32 = 6;
+
+/// Migrations upgrading an aliases document, the migration at index `n`
+/// upgrades a document from version `n` to version `n + 1`
+const MIGRATIONS: &[fn(&mut Document)] = &[
+    migrate_alias_map,
+    // Version 1 to 2: aliases may be tables with a description and tags
+    unchanged,
+    // Version 2 to 3: paths which are not valid UTF-8 may be stored escaped
+    unchanged,
+    // Version 3 to 4: aliases may list several candidate paths
+    unchanged,
+    // Version 4 to 5: aliases may be overridden in `[hosts.<name>]` sections
+    unchanged,
+    migrate_environment,
+];
+
+/// Prefix of the environment variables of aliases unless
+/// [`Aliases::set_env_prefix`] is used
+pub const DEFAULT_ENV_PREFIX: &str = "RAD_";
+
+/// Variables an alias variable must never override, compared ignoring case
+/// since zsh ties `path` to `PATH`
+const CRITICAL_VARS: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "PWD",
+    "OLDPWD",
+    "IFS",
+    "PS1",
+    "PS2",
+    "PS4",
+    "TERM",
+    "LANG",
+    "TMPDIR",
+    "CDPATH",
+    "FPATH",
+    "MANPATH",
+    "LD_LIBRARY_PATH",
+    "LD_PRELOAD",
+    "DYLD_LIBRARY_PATH",
+];
+
+/// Order in which aliases are listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    /// Alphabetical order of aliases
+    Name,
+    /// Most frequently and recently visited aliases first
+    Frecency,
+}
+
+impl Sort {
+    /// Names accepted by `from_str`
+    pub const VARIANTS: &'static [&'static str] = &["name", "frecency"];
+}
+
+impl std::str::FromStr for Sort {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(Sort::Name),
+            "frecency" => Ok(Sort::Frecency),
+            _ => Err(format!("unknown sort order '{}'", s)),
+        }
+    }
+}
+
+/// How strictly a name has to match an alias to select it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Matching {
+    /// The name is the alias
+    Exact,
+    /// The name is the beginning of a single alias
+    Prefix,
+    /// The letters of the name appear in order in a single alias,
+    /// ignoring case
+    Fuzzy,
+}
+
+impl Matching {
+    /// Names accepted by `from_str`
+    pub const VARIANTS: &'static [&'static str] = &["exact", "prefix", "fuzzy"];
+}
+
+impl std::str::FromStr for Matching {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "exact" => Ok(Matching::Exact),
+            "prefix" => Ok(Matching::Prefix),
+            "fuzzy" => Ok(Matching::Fuzzy),
+            _ => Err(format!("unknown matching '{}'", s)),
+        }
+    }
+}
+
 /// Contain aliases and assiociated path
 /// ```
 /// use serde_derive::{Serialize,Deserialize};
 /// use std::collections::BTreeMap;
+/// # use rualdlib::AliasEntry;
 ///
 /// #[derive(Serialize, Deserialize, Debug)]
 /// pub struct Aliases {
-///     aliases: Option<BTreeMap<String, String>>,
+///     aliases: Option<BTreeMap<String, AliasEntry>>,
 /// }
 /// ```
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Aliases {
-    pub aliases: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    pub version: u32,
+    pub aliases: Option<BTreeMap<String, AliasEntry>>,
     #[serde(rename = "environment")]
-    pub vars: Option<BTreeMap<String, String>>,
+    pub vars: Option<BTreeMap<String, EnvEntry>>,
     pub colors: Option<BTreeMap<String, String>>,
     pub alias_hash: Option<BTreeMap<String, String>>,
+    pub hosts: Option<BTreeMap<String, HostSection>>,
+    #[serde(skip)]
+    origins: hosts::Origins,
+    #[serde(skip, default = "default_env_prefix")]
+    env_prefix: String,
+    #[serde(skip)]
+    env_case: EnvCase,
+    #[serde(skip)]
+    physical_paths: bool,
+    #[serde(skip)]
+    read_only: bool,
     #[serde(skip)]
     modified: bool,
+    /// Visits to move, `(alias, new_alias)`, once the aliases are saved
+    #[serde(skip)]
+    moved_visits: Vec<(String, Option<String>)>,
     #[serde(skip)]
     aliases_file: PathBuf,
+    #[serde(skip)]
+    lock: Option<fs::File>,
+    #[serde(skip)]
+    document: Document,
 }
 
 /// Get alias from rad TOML structure
 /// ```
-/// # use anyhow::Result;
+/// # use rualdlib::{Aliases, Result};
 /// # fn main() -> Result<()> {
-/// # use rualdlib::Aliases;
 /// let home = std::env::var("HOME").unwrap();
 /// let aliases: Aliases = toml::from_str(r#"
 /// [aliases]
@@ -60,7 +200,7 @@ pub struct Aliases {
 /// "#).unwrap();
 ///
 /// let alias = "Home";
-/// let pointed = aliases.get(alias);
+/// let pointed = aliases.get(alias)?;
 ///
 /// assert_eq!(pointed, Some(home));
 /// # Ok(())
@@ -70,102 +210,239 @@ impl Aliases {
     /// Open rualdi aliases file from default aliases directory,
     /// default directory can be configured by _RAD_ALIASES_DIR
     /// env variable.
+    ///
+    /// A shared advisory lock is taken on the aliases directory and held
+    /// until the aliases are dropped, it is only upgraded to an exclusive
+    /// one while the file is written, see [`Aliases::save`].
+    ///
+    /// Files written with an older layout are upgraded to [`CONFIG_VERSION`],
+    /// the original file is kept next to it as `rualdi.toml.v<version>.bak`.
+    /// Files written by a newer version of rualdi are refused.
+    ///
+    /// Aliases of the `[hosts.<name>]` sections matching this machine are
+    /// applied over the base ones, see [`HostSection`].
     pub fn open(aliases_dir: PathBuf) -> Result<Self> {
-        fs::create_dir_all(&aliases_dir).with_context(|| {
-            format!(
-                "unable to create config directory: '{}'",
-                aliases_dir.display()
-            )
-        })?;
+        fs::create_dir_all(&aliases_dir).map_err(Error::io(format!(
+            "unable to create config directory: '{}'",
+            aliases_dir.display()
+        )))?;
 
+        let lock = Self::lock(&aliases_dir)?;
         let path = Self::get_path(&aliases_dir);
 
-        if !path.is_file() {
-            let default_file = r#"# Rualdi aliases configuration file
-# DO NOT EDIT VARIABLES AND ALIASES
+        let (content, document) = match Self::read(&path)? {
+            Some((content, document))
+                if Self::version(&path, &document)? == CONFIG_VERSION as i64 =>
+            {
+                (content, document)
+            }
+            // Another process may create or upgrade the file meanwhile,
+            // so it is read again once the lock is exclusive
+            _ => Self::exclusive(Some(&lock), &path, || Self::upgrade(&path))?,
+        };
+
+        let mut aliases = Self::load(path, &content, document)?;
+        aliases.lock = Some(lock);
+        Ok(aliases)
+    }
+
+    /// Open rualdi aliases file as `open` does, for commands which only
+    /// read aliases and must stay fast, such as prompt hooks. No lock is
+    /// taken and the file is never written: a missing file holds the default
+    /// aliases and an older layout is only upgraded in memory. Changes made
+    /// to aliases opened this way are not saved.
+    pub fn open_read_only(aliases_dir: PathBuf) -> Result<Self> {
+        let path = Self::get_path(&aliases_dir);
+        let (mut content, mut document) = match Self::read(&path)? {
+            Some(read) => read,
+            None => {
+                let content = Self::default_content();
+                let document = content.parse::<Document>().map_err(Error::parse(&path))?;
+                (content, document)
+            }
+        };
+        let version = Self::version(&path, &document)?;
+        if version < CONFIG_VERSION as i64 {
+            migrate(&mut document, version);
+            content = document.to_string();
+        }
+
+        let mut aliases = Self::load(path, &content, document)?;
+        aliases.read_only = true;
+        Ok(aliases)
+    }
+
+    /// Build the aliases of the file at path from its content
+    fn load(path: PathBuf, content: &str, document: Document) -> Result<Self> {
+        let mut aliases: Aliases = toml::from_str(content).map_err(Error::parse(&path))?;
+        aliases.apply_hosts(hosts::hostname().as_deref());
+        aliases.document = document;
+        aliases.modified = false;
+        aliases.aliases_file = path;
+        Ok(aliases)
+    }
+
+    /// Content of the aliases file created when it does not exist
+    fn default_content() -> String {
+        format!(
+            r#"# Rualdi aliases configuration file
+version = {}
+
 [colors]
 alias = "yellow"
 separator = "bright cyan"
 path = "magenta"
-"#;
-            let mut aliases_file: fs::File = fs::OpenOptions::new()
-                .write(true)
-                .create(true)
-                .open(&path)
-                .with_context(|| format!("could not create alias file: '{}'", path.display()))?;
+"#,
+            CONFIG_VERSION
+        )
+    }
+
+    /// Create the aliases file at path if missing or migrate it to
+    /// [`CONFIG_VERSION`], returning its new content
+    fn upgrade(path: &Path) -> Result<(String, Document)> {
+        let (content, mut document) = match Self::read(path)? {
+            Some(read) => read,
+            None => {
+                let content = Self::default_content();
+                Self::write_atomic(path, &content)?;
+                let document = content.parse::<Document>().map_err(Error::parse(path))?;
+                return Ok((content, document));
+            }
+        };
 
-            aliases_file
-                .write_all(default_file.as_bytes())
-                .with_context(|| format!("could not create alias file: '{}'", path.display()))?;
-            aliases_file.flush()?;
+        let version = Self::version(path, &document)?;
+        if version == CONFIG_VERSION as i64 {
+            return Ok((content, document));
         }
 
-        let mut aliases_file = fs::File::open(&path)
-            .with_context(|| format!("could not open alias file: '{}'", path.display()))?;
+        let backup = path.with_extension(format!("toml.v{}.bak", version));
+        fs::copy(path, &backup).map_err(Error::io(format!(
+            "could not backup alias file: '{}'",
+            backup.display()
+        )))?;
+        migrate(&mut document, version);
 
-        let mut content = String::new();
+        let content = document.to_string();
+        Self::write_atomic(path, &content)?;
+        Ok((content, document))
+    }
+
+    /// Read and parse the aliases file at path, `None` if it does not exist
+    fn read(path: &Path) -> Result<Option<(String, Document)>> {
+        if !path.is_file() {
+            return Ok(None);
+        }
 
-        aliases_file.read_to_string(&mut content)?;
+        let mut aliases_file = fs::File::open(path).map_err(Error::io(format!(
+            "could not open alias file: '{}'",
+            path.display()
+        )))?;
 
-        let mut aliases: Aliases = toml::from_str(&content)
-            .with_context(|| format!("could not open alias file: '{}'", path.display()))?;
-        aliases.modified = false;
-        aliases.aliases_file = path;
-        Ok(aliases)
+        let mut content = String::new();
+
+        aliases_file
+            .read_to_string(&mut content)
+            .map_err(Error::io(format!(
+                "could not read alias file: '{}'",
+                path.display()
+            )))?;
+
+        let document = content.parse::<Document>().map_err(Error::parse(path))?;
+        Ok(Some((content, document)))
+    }
+
+    /// Get the layout version of document, raise an error if it was
+    /// written by a newer version of rualdi
+    fn version(path: &Path, document: &Document) -> Result<i64> {
+        let version = document
+            .get("version")
+            .and_then(Item::as_integer)
+            .unwrap_or(0);
+        if version > CONFIG_VERSION as i64 {
+            return Err(Error::UnsupportedVersion {
+                path: path.to_path_buf(),
+                found: version,
+                supported: CONFIG_VERSION,
+            });
+        }
+        Ok(version)
     }
 
     /// Save rualdi aliases file in default aliases directory,
     /// default directory can be configured by _RAD_ALIASES_DIR
     /// env variable.
+    ///
+    /// The file is read again under an exclusive lock and the changes made
+    /// since `open` are applied to it, so comments, key order and layout
+    /// of untouched entries are kept, as well as the changes saved by other
+    /// processes meanwhile. The new content is written to a temporary file
+    /// which is then renamed over the original, so an interrupted save
+    /// never leaves a truncated configuration file behind.
     pub fn save(&self) -> Result<()> {
-        if !self.modified {
-            Ok(())
-        } else {
-            let mut aliases_file: fs::File = fs::OpenOptions::new()
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(&self.aliases_file)
-                .with_context(|| {
-                    format!(
-                        "could not save alias file: '{}'",
-                        &self.aliases_file.display()
-                    )
-                })?;
-            aliases_file.sync_data()?;
+        if !self.modified || self.read_only {
+            return Ok(());
+        }
+        Self::exclusive(self.lock.as_ref(), &self.aliases_file, || {
+            let mut document = match Self::read(&self.aliases_file)? {
+                Some((_, document)) => document,
+                None => self.document.clone(),
+            };
+            let original: toml::Value = toml::from_str(&self.document.to_string())
+                .map_err(Error::parse(&self.aliases_file))?;
+            let mut updated = toml::Value::try_from(self)?;
+            self.origins.split(&mut updated)?;
+            restore_datetimes(&mut updated);
+
+            if let (Some(original), Some(updated)) = (original.as_table(), updated.as_table()) {
+                merge_table(document.as_table_mut(), original, updated, true);
+            }
 
             let mut content = String::new();
-            content.push_str(
-                "# Rualdi aliases configuration file\n# DO NOT EDIT VARIABLES AND ALIASES\n",
-            );
-
-            let data = toml::to_string(&self).with_context(|| "fail to encode aliases in toml")?;
-            content.push_str(data.as_str());
-            aliases_file
-                .write_all(content.as_bytes())
-                .with_context(|| {
-                    format!(
-                        "fail to save aliases file '{}'",
-                        self.aliases_file.display()
-                    )
-                })?;
-            aliases_file.sync_all()?;
-            Ok(())
+            if document.to_string().trim().is_empty() {
+                content.push_str("# Rualdi aliases configuration file\n");
+            }
+            content.push_str(&document.to_string());
+            Self::write_atomic(&self.aliases_file, &content)?;
+            self.apply_moved_visits()
+        })
+    }
+
+    /// Apply the host sections matching `hostname` over the base aliases
+    fn apply_hosts(&mut self, hostname: Option<&str>) {
+        if let Some(hosts) = &self.hosts {
+            let mut aliases = self.aliases.take().unwrap_or_default();
+            self.origins = hosts::Origins::apply(hosts, hostname, &mut aliases);
+            self.aliases = Some(aliases);
         }
     }
 
+    /// Get the host section alias comes from, `None` for the base aliases
+    pub fn origin(&self, alias: &str) -> Option<&str> {
+        self.origins.get(alias)
+    }
+
     /// Add alias on path in aliase configuration file, raise an error if alias
     /// already exists.
     pub fn add(&mut self, alias: String, path: String) -> Result<()> {
+        self.add_entry(alias, AliasEntry::new(path))
+    }
+
+    /// Add alias with its metadata in aliase configuration file, raise an
+    /// error if alias already exists. Creation and modification dates are
+    /// recorded for entries carrying a description or tags.
+    pub fn add_entry(&mut self, alias: String, mut entry: AliasEntry) -> Result<()> {
         let mut aliases = self.selfmatch(self.aliases.to_owned());
         let colors = self.selfmatch(self.colors.to_owned());
         let alias_hash = self.selfmatch(self.alias_hash.to_owned());
 
+        validate_alias(&alias)?;
         if aliases.contains_key(&alias) {
-            return Err(anyhow!("alias '{}' already exists", alias));
+            return Err(Error::AliasExists(alias));
         }
 
-        aliases.insert(alias, path);
+        entry.touch();
+        aliases.insert(alias.to_owned(), entry);
+        check_references(&aliases, &alias)?;
 
         self.aliases = Some(aliases);
         self.alias_hash = Some(alias_hash);
@@ -174,31 +451,60 @@ path = "magenta"
         Ok(())
     }
 
+    /// Point an existing alias on a new path, its metadata and environment
+    /// variable are kept. Return the previous path, raise an error if alias
+    /// not exists.
+    pub fn update(&mut self, alias: String, path: String) -> Result<String> {
+        self.update_entry(alias, AliasEntry::new(path))
+    }
+
+    /// Point an existing alias on the path held by `path` as [`update`](Self::update)
+    /// does, used for paths which are not valid UTF-8 built with
+    /// [`AliasEntry::from_path`]. Metadata of `path` are ignored.
+    pub fn update_entry(&mut self, alias: String, path: AliasEntry) -> Result<String> {
+        let mut aliases = self.selfmatch(self.aliases.to_owned());
+        let colors = self.selfmatch(self.colors.to_owned());
+        let alias_hash = self.selfmatch(self.alias_hash.to_owned());
+
+        let entry = match aliases.get_mut(&alias) {
+            Some(entry) => entry,
+            None => return Err(Error::AliasNotFound(alias)),
+        };
+        let old_path = entry.set_path(path).path_lossy().into_owned();
+        entry.touch();
+        check_references(&aliases, &alias)?;
+
+        self.aliases = Some(aliases);
+        self.alias_hash = Some(alias_hash);
+        self.colors = Some(colors);
+        self.modified = true;
+        Ok(old_path)
+    }
+
     /// Add environment variable assiociated with an alias configuration file
     /// to load it in shell environment, raise an error if environment variable
-    /// already exists.
+    /// already exists. An alias can have several variables, `alias` may be
+    /// followed by a subpath as `alias/sub`.
     pub fn add_env(&mut self, alias: String, var_name: String) -> Result<()> {
+        self.add_env_entry(var_name, EnvEntry::new(alias))
+    }
+
+    /// Same as `add_env` with an entry, to export the variable without prefix
+    pub fn add_env_entry(&mut self, var_name: String, entry: EnvEntry) -> Result<()> {
         let mut vars = self.selfmatch(self.vars.to_owned());
         let colors = self.selfmatch(self.colors.to_owned());
         let alias_hash = self.selfmatch(self.alias_hash.to_owned());
 
-        if vars.contains_key(&alias) {
-            return Err(anyhow!(
-                "alias '{}' has already a environment variable assiociated",
-                alias
-            ));
-        }
+        self.check_env_var(&var_name, entry.prefixed)?;
 
-        let values: Vec<String> = vars.values().cloned().collect();
-        if values.contains(&var_name) {
-            return Err(anyhow!(
-                "environment variable '{}' for alias '{}' already exists",
-                var_name,
-                alias
-            ));
+        if vars.contains_key(&var_name) {
+            return Err(Error::EnvVarTaken {
+                alias: entry.alias().into(),
+                var: var_name,
+            });
         }
 
-        vars.insert(alias, var_name);
+        vars.insert(var_name, entry);
 
         self.vars = Some(vars);
         self.alias_hash = Some(alias_hash);
@@ -208,152 +514,361 @@ path = "magenta"
     }
 
     /// Remove alias on path in aliase configuration file, raise an error if alias
-    /// not exists.
-    pub fn remove(&mut self, alias: String) -> Result<()> {
+    /// not exists. Removing an alias of a host section which hides a base alias
+    /// makes the base alias active again, it is returned.
+    pub fn remove(&mut self, alias: String) -> Result<Option<AliasEntry>> {
         let mut aliases = self.selfmatch(self.aliases.to_owned());
         let colors = self.selfmatch(self.colors.to_owned());
         let alias_hash = self.selfmatch(self.alias_hash.to_owned());
 
         if !aliases.contains_key(&alias) {
-            return Err(anyhow!("alias '{}' not exists", alias));
+            return Err(Error::AliasNotFound(alias));
         }
 
         aliases.remove(&alias);
+        let base = self.origins.remove(&alias);
+        match &base {
+            Some(base) => {
+                aliases.insert(alias, base.to_owned());
+            }
+            None => self.moved_visits.push((alias, None)),
+        }
+
+        self.aliases = Some(aliases);
+        self.alias_hash = Some(alias_hash);
+        self.colors = Some(colors);
+        self.modified = true;
+        Ok(base)
+    }
+
+    /// Rename alias keeping its path and metadata, the environment
+    /// variables associated are moved to the new alias and the variable
+    /// is renamed to `var` if provided and the alias has a single one.
+    /// Raise an error if alias not exists or if the new alias already exists.
+    pub fn rename(&mut self, alias: String, new_alias: String, var: Option<String>) -> Result<()> {
+        let mut aliases = self.selfmatch(self.aliases.to_owned());
+        let mut vars = self.selfmatch(self.vars.to_owned());
+        let colors = self.selfmatch(self.colors.to_owned());
+        let alias_hash = self.selfmatch(self.alias_hash.to_owned());
+
+        validate_alias(&new_alias)?;
+        if !aliases.contains_key(&alias) {
+            return Err(Error::AliasNotFound(alias));
+        }
+        if aliases.contains_key(&new_alias) {
+            return Err(Error::AliasExists(new_alias));
+        }
+
+        if let Some(var) = var {
+            let owned = owned_vars(&vars, &alias);
+            let old_var = match owned.as_slice() {
+                [] => return Err(Error::EnvVarNotFound(alias)),
+                [old_var] => old_var.to_owned(),
+                _ => return Err(Error::SeveralEnvVars(alias)),
+            };
+            let entry = vars.remove(&old_var).unwrap();
+            self.check_env_var(&var, entry.prefixed)?;
+            if vars.contains_key(&var) {
+                return Err(Error::EnvVarTaken {
+                    alias: new_alias,
+                    var,
+                });
+            }
+            vars.insert(var, entry);
+        }
+        for entry in vars.values_mut() {
+            if entry.alias() == alias {
+                entry.retarget(&new_alias);
+            }
+        }
+
+        let mut entry = aliases.remove(&alias).unwrap_or_default();
+        entry.touch();
+        aliases.insert(new_alias.to_owned(), entry);
+        self.origins.rename(&alias, &new_alias);
+        self.moved_visits
+            .push((alias.to_owned(), Some(new_alias.to_owned())));
+
+        // Keep references to the renamed alias pointing on it
+        let reference = format!("@{}", alias);
+        for entry in aliases.values_mut() {
+            let paths = std::iter::once(&mut entry.path).chain(entry.alternatives.iter_mut());
+            for path in paths {
+                if let Some(sub) = path.strip_prefix(&reference) {
+                    if sub.is_empty() || sub.starts_with('/') {
+                        *path = format!("@{}{}", new_alias, sub);
+                    }
+                }
+            }
+        }
 
         self.aliases = Some(aliases);
+        self.vars = Some(vars);
         self.alias_hash = Some(alias_hash);
         self.colors = Some(colors);
         self.modified = true;
         Ok(())
     }
 
-    /// Remove environment variable associated to an alias
-    /// in aliase configuration file, raise an error if variable
+    /// Remove environment variables `names` associated to an alias, or all
+    /// of them if `names` is empty, in aliase configuration file. Return the
+    /// variables removed with their entries, raise an error if a variable
     /// not exists.
-    pub fn remove_env(&mut self, alias: String) -> Result<()> {
+    pub fn remove_env(
+        &mut self,
+        alias: String,
+        names: &[String],
+    ) -> Result<Vec<(String, EnvEntry)>> {
         let mut vars = self.selfmatch(self.vars.to_owned());
         let colors = self.selfmatch(self.colors.to_owned());
         let alias_hash = self.selfmatch(self.alias_hash.to_owned());
 
-        if !vars.contains_key(&alias) {
-            return Err(anyhow!(
-                "no such environment variable for alias '{}'",
-                alias
-            ));
+        let owned = owned_vars(&vars, &alias);
+        let removed = if names.is_empty() {
+            owned
+        } else if names.iter().all(|name| owned.contains(name)) {
+            names.to_vec()
+        } else {
+            Vec::new()
+        };
+        if removed.is_empty() {
+            return Err(Error::EnvVarNotFound(alias));
         }
 
-        vars.remove(&alias);
+        let removed = removed
+            .into_iter()
+            .filter_map(|var| vars.remove(&var).map(|entry| (var, entry)))
+            .collect();
 
         self.vars = Some(vars);
         self.alias_hash = Some(alias_hash);
         self.colors = Some(colors);
         self.modified = true;
-        Ok(())
+        Ok(removed)
     }
 
     /// Get a formatted String conaining aliases/paths
-    /// found in configuration file
-    pub fn list(&self) -> Option<String> {
-        if let Some(aliases) = &self.aliases {
-            if aliases.is_empty() {
-                None
-            } else {
-                // TODO: test invalid environment var
-                // TODO: test no environment vars with header section
-                // TODO: test no environment vars without header section
-                // If default is off and there are no aliases listed, insert the home directory
-                // mapping with itself to prevent errors
-                let alias_hash = self.build_alias_hash().unwrap_or_else(|| {
-                    let mut tmp = IndexMap::new();
-                    tmp.insert(
-                        dirs::home_dir()
-                            .unwrap_or(PathBuf::from("INVALID_HOME_DIR"))
-                            .into_os_string()
-                            .into_string()
-                            .unwrap(),
-                        dirs::home_dir()
-                            .unwrap_or(PathBuf::from("INVALID_HOME_DIR"))
-                            .into_os_string()
-                            .into_string()
-                            .unwrap(),
-                    );
-                    tmp
-                });
+    /// found in configuration file, only aliases carrying
+    /// every tag of `tags` are listed
+    pub fn list(&self, tags: &[String], sort: Sort) -> Option<String> {
+        let aliases = self.tagged(tags, sort);
+        if aliases.is_empty() {
+            None
+        } else {
+            let mut res = Self::banner("ALIASES");
+            res.push_str(&self.format_aliases(&aliases));
+            res.push_str(&self.format_vars(tags, &aliases));
+            Some(res)
+        }
+    }
 
-                let mut reg = Vec::new();
-                for (k, _) in alias_hash.iter() {
-                    reg.push(k.to_string());
+    /// Same as `list` with a section per tag, aliases without
+    /// tags are listed in a last section
+    pub fn list_grouped(&self, tags: &[String], sort: Sort) -> Option<String> {
+        let aliases = self.tagged(tags, sort);
+        if aliases.is_empty() {
+            None
+        } else {
+            let mut groups: BTreeMap<&str, Vec<(&String, &AliasEntry)>> = BTreeMap::new();
+            let mut untagged = Vec::new();
+            for &(alias, entry) in aliases.iter() {
+                if entry.tags.is_empty() {
+                    untagged.push((alias, entry));
+                }
+                for tag in entry.tags.iter() {
+                    groups.entry(tag).or_default().push((alias, entry));
                 }
+            }
 
-                let width = terminal_size().map(|(w, _)| w.0 as usize).unwrap_or(1);
-                let equal_line = "=".repeat(width).green().bold();
-                let mut res = String::new();
+            let mut res = String::new();
+            for (tag, group) in groups.iter() {
+                res.push_str(&Self::banner(&format!("TAG: {}", tag)));
+                res.push_str(&self.format_aliases(group));
+            }
+            if !untagged.is_empty() {
+                res.push_str(&Self::banner("UNTAGGED"));
+                res.push_str(&self.format_aliases(&untagged));
+            }
+            res.push_str(&self.format_vars(tags, &aliases));
+            Some(res)
+        }
+    }
 
-                res.push_str(
-                    format!(
-                        "{}\n{: ^width$}\n{}\n",
-                        equal_line,
-                        "ALIASES".red().bold(),
-                        equal_line,
-                        width = width - 1
-                    )
-                    .as_str(),
-                );
+    /// Get aliases carrying every tag of `tags` in `sort` order
+    fn tagged(&self, tags: &[String], sort: Sort) -> Vec<(&String, &AliasEntry)> {
+        let mut aliases: Vec<_> = match &self.aliases {
+            Some(aliases) => aliases
+                .iter()
+                .filter(|(_, entry)| entry.has_tags(tags))
+                .collect(),
+            None => Vec::new(),
+        };
+        if sort == Sort::Frecency {
+            // An unreadable visits file only loses the ranking
+            let visits = self.visits().unwrap_or_default();
+            let now = Utc::now().timestamp();
+            aliases.sort_by(|(a, _), (b, _)| {
+                visits
+                    .frecency(b, now)
+                    .partial_cmp(&visits.frecency(a, now))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+        aliases
+    }
+
+    /// Get a section title surrounded by lines as wide as the terminal
+    fn banner(title: &str) -> String {
+        let width = terminal_size().map(|(w, _)| w.0 as usize).unwrap_or(1);
+        let equal_line = "=".repeat(width).green().bold();
+        format!(
+            "{}\n{: ^width$}\n{}\n",
+            equal_line,
+            title.red().bold(),
+            equal_line,
+            width = width - 1
+        )
+    }
+
+    /// Get aliases formatted one per line, paths are shortened with the alias hash
+    fn format_aliases(&self, aliases: &[(&String, &AliasEntry)]) -> String {
+        // TODO: test invalid environment var
+        // TODO: test no environment vars with header section
+        // TODO: test no environment vars without header section
+        // If default is off and there are no aliases listed, insert the home directory
+        // mapping with itself to prevent errors
+        let alias_hash = self.build_alias_hash().unwrap_or_else(|| {
+            let mut tmp = IndexMap::new();
+            tmp.insert(
+                dirs::home_dir()
+                    .unwrap_or(PathBuf::from("INVALID_HOME_DIR"))
+                    .into_os_string()
+                    .into_string()
+                    .unwrap(),
+                dirs::home_dir()
+                    .unwrap_or(PathBuf::from("INVALID_HOME_DIR"))
+                    .into_os_string()
+                    .into_string()
+                    .unwrap(),
+            );
+            tmp
+        });
+
+        let mut reg = Vec::new();
+        for (k, _) in alias_hash.iter() {
+            reg.push(k.to_string());
+        }
+
+        let color_alias = self.get_colors("alias").unwrap_or(Color::Yellow);
+        let color_separator = self.get_colors("separator").unwrap_or(Color::BrightCyan);
+        let color_path = self.get_colors("path").unwrap_or(Color::Magenta);
+
+        let mut res = String::new();
+        let re = Regex::new(format!(r"({})", reg.join("|")).as_str()).unwrap();
+        let shorten = |path: &str| -> String {
+            if re.is_match(path) {
+                re.replace(path, |caps: &Captures| {
+                    alias_hash.get(caps.get(1).unwrap().as_str()).unwrap()
+                })
+                .into_owned()
+            } else {
+                path.to_owned()
+            }
+        };
+        for (alias, entry) in aliases.iter() {
+            let mut new_path = shorten(&entry.path_lossy());
+            // Candidate paths are followed by the one used on this host
+            if !entry.alternatives.is_empty() {
+                let candidates = entry
+                    .alternatives
+                    .iter()
+                    .map(|path| shorten(path))
+                    .collect::<Vec<_>>();
+                new_path = format!("{} | {}", new_path, candidates.join(" | "));
+                let active = match self.get_raw(alias) {
+                    Ok(Some(path)) if path.exists() => {
+                        format!("(active: {})", shorten(&path.to_string_lossy())).dimmed()
+                    }
+                    Ok(_) => "(no candidate exists)".red(),
+                    Err(e) => format!("({})", e).red(),
+                };
+                new_path = format!("{} {}", new_path, active);
+            // References to other aliases are followed by their expanded path
+            } else if entry.path.starts_with('@') {
+                let expanded = match self.get(alias) {
+                    Ok(path) => format!("({})", shorten(&path.unwrap_or_default())).dimmed(),
+                    Err(e) => format!("({})", e).red(),
+                };
+                new_path = format!("{} {}", new_path, expanded);
+            }
+            let origin = self
+                .origin(alias)
+                .map(|name| format!(" [host: {}]", name).dimmed().to_string())
+                .unwrap_or_default();
+            let description = entry
+                .description
+                .as_ref()
+                .map(|d| format!("  # {}", d).dimmed().to_string())
+                .unwrap_or_default();
+            res.push_str(
+                format!(
+                    "{:<12} {:<2} {}{}{}\n",
+                    alias.color(color_alias).bold(),
+                    "=>".color(color_separator).bold(),
+                    new_path.color(color_path),
+                    origin,
+                    description
+                )
+                .as_str(),
+            );
+        }
+        res
+    }
 
+    /// Get the environment variables section, only for the listed aliases
+    /// when filtered by `tags`, empty if there is no variable to show.
+    /// Without tags, variables of aliases which no longer exist are shown too.
+    fn format_vars(&self, tags: &[String], aliases: &[(&String, &AliasEntry)]) -> String {
+        let mut res = String::new();
+        if let Some(vars) = &self.vars {
+            let vars = vars
+                .iter()
+                .filter(|(_, entry)| {
+                    tags.is_empty() || aliases.iter().any(|(listed, _)| *listed == entry.alias())
+                })
+                .collect::<Vec<_>>();
+            if !vars.is_empty() {
                 let color_alias = self.get_colors("alias").unwrap_or(Color::Yellow);
                 let color_separator = self.get_colors("separator").unwrap_or(Color::BrightCyan);
                 let color_path = self.get_colors("path").unwrap_or(Color::Magenta);
 
-                let re = Regex::new(format!(r"({})", reg.join("|")).as_str()).unwrap();
-                for (alias, path) in aliases.iter() {
-                    let new_path = if re.is_match(path) {
-                        re.replace(path, |caps: &Captures| {
-                            alias_hash.get(caps.get(1).unwrap().as_str()).unwrap()
-                        })
+                res.push_str(&Self::banner("ENVIRONMENT VARIABLES"));
+                for (var, entry) in vars {
+                    let no_prefix = if entry.prefixed {
+                        String::new()
                     } else {
-                        Cow::from(path)
+                        " (no prefix)".dimmed().to_string()
+                    };
+                    // Names which cannot be exported are followed by a valid one
+                    let invalid = match self.check_env_var(var, entry.prefixed) {
+                        Ok(()) => String::new(),
+                        Err(e) => format!(" ({})", e).red().to_string(),
                     };
                     res.push_str(
                         format!(
-                            "{:<12} {:<2} {}\n",
-                            alias.color(color_alias).bold(),
+                            "{:<12} {:<2} {}{}{}\n",
+                            var.color(color_alias).bold(),
                             "=>".color(color_separator).bold(),
-                            new_path.color(color_path)
+                            entry.target.color(color_path),
+                            no_prefix,
+                            invalid
                         )
                         .as_str(),
                     );
                 }
-
-                if let Some(vars) = &self.vars {
-                    if !vars.is_empty() {
-                        res.push_str(
-                            format!(
-                                "{}\n{: ^width$}\n{}\n",
-                                equal_line,
-                                "ENVIRONMENT VARIABLES".red().bold(),
-                                equal_line,
-                                width = width - 1
-                            )
-                            .as_str(),
-                        );
-                        for (alias, var) in vars.iter() {
-                            res.push_str(
-                                format!(
-                                    "{:<12} {:<2} {}\n",
-                                    var.color(color_alias).bold(),
-                                    "=>".color(color_separator).bold(),
-                                    alias.color(color_path)
-                                )
-                                .as_str(),
-                            );
-                        }
-                    }
-                }
-                Some(res)
             }
-        } else {
-            None
         }
+        res
     }
 
     /// Get a String conaining aliases/vars
@@ -364,11 +879,11 @@ path = "magenta"
             if vars.is_empty() {
                 vars_found
             } else {
-                for (alias, var) in vars.iter() {
+                for (var, entry) in vars.iter() {
                     vars_found.push_str(
                         format!(
                             "{} {} {}\n",
-                            alias.yellow(),
+                            entry.target.yellow(),
                             "=>".bright_cyan(),
                             var.magenta()
                         )
@@ -382,35 +897,45 @@ path = "magenta"
         }
     }
 
-    pub fn list_alias(&self) -> Option<String> {
-        if let Some(aliases) = &self.aliases {
-            if aliases.is_empty() {
-                None
-            } else {
-                let mut res = String::new();
-                for (alias, path) in aliases.iter() {
-                    res.push_str(format!("{} => {}\n", alias, path).as_str());
-                }
-                Some(res)
-            }
-        } else {
+    /// Get aliases carrying every tag of `tags` with their path, not colored
+    pub fn list_alias(&self, tags: &[String], sort: Sort) -> Option<String> {
+        let aliases = self.tagged(tags, sort);
+        if aliases.is_empty() {
             None
+        } else {
+            let mut res = String::new();
+            for (alias, entry) in aliases.iter() {
+                // References to other aliases and candidate paths are listed expanded
+                let path = if entry.path.starts_with('@') || !entry.alternatives.is_empty() {
+                    self.get(alias).ok().flatten()
+                } else {
+                    None
+                };
+                let path = path.unwrap_or_else(|| entry.path_lossy().into_owned());
+                res.push_str(format!("{} => {}\n", alias, path).as_str());
+            }
+            Some(res)
         }
     }
 
-    pub fn list_alias_completions(&self) -> Option<String> {
-        if let Some(aliases) = &self.aliases {
-            if aliases.is_empty() {
-                None
-            } else {
-                let mut res = String::new();
-                for (alias, _) in aliases.iter() {
-                    res.push_str(format!("{}\n", alias).as_str());
+    /// Get aliases carrying every tag of `tags` formatted for zsh `_describe`,
+    /// one `alias:description` per line, colons in aliases are escaped
+    pub fn list_alias_completions(&self, tags: &[String]) -> Option<String> {
+        let aliases = self.tagged(tags, Sort::Name);
+        if aliases.is_empty() {
+            None
+        } else {
+            let mut res = String::new();
+            for (alias, entry) in aliases.iter() {
+                let alias = alias.replace(':', "\\:");
+                match &entry.description {
+                    Some(description) => {
+                        res.push_str(format!("{}:{}\n", alias, description).as_str())
+                    }
+                    None => res.push_str(format!("{}\n", alias).as_str()),
                 }
-                Some(res)
             }
-        } else {
-            None
+            Some(res)
         }
     }
 
@@ -420,7 +945,7 @@ path = "magenta"
                 None
             } else {
                 let mut res = String::new();
-                for (_, var) in vars.iter() {
+                for var in vars.keys() {
                     res.push_str(format!("{}\n", var).as_str());
                 }
                 Some(res)
@@ -430,17 +955,152 @@ path = "magenta"
         }
     }
 
+    /// Get visits of aliases recorded by `record_visit`
+    pub fn visits(&self) -> Result<Visits> {
+        Visits::load(&self.visits_file())
+    }
+
+    /// Record a jump to alias in the visits file kept next to the
+    /// aliases file, raise an error if alias not exists.
+    pub fn record_visit(&self, alias: &str) -> Result<()> {
+        if !self.contains(alias) {
+            return Err(Error::AliasNotFound(alias.into()));
+        }
+        let path = self.visits_file();
+        // Read and written under the exclusive lock so that concurrent
+        // jumps are all counted
+        Self::exclusive(self.lock.as_ref(), &path, || {
+            let mut visits = Visits::load(&path)?;
+            visits.record(alias, Utc::now().timestamp());
+            Self::write_atomic(&path, &toml::to_string(&visits)?)
+        })
+    }
+
+    /// Move the visits of renamed aliases to their new name in the visits
+    /// file and drop the ones of removed aliases, called by `save` under
+    /// the exclusive lock once the aliases are written
+    fn apply_moved_visits(&self) -> Result<()> {
+        if self.moved_visits.is_empty() {
+            return Ok(());
+        }
+        let path = self.visits_file();
+        let mut visits = Visits::load(&path)?;
+        let mut changed = false;
+        for (alias, new_alias) in &self.moved_visits {
+            if let Some(visit) = visits.remove(alias) {
+                if let Some(new_alias) = new_alias {
+                    visits.insert(new_alias, visit);
+                }
+                changed = true;
+            }
+        }
+        if changed {
+            Self::write_atomic(&path, &toml::to_string(&visits)?)?;
+        }
+        Ok(())
+    }
+
+    /// Get a report of the visits of every alias, most used first
+    pub fn stats(&self) -> Option<String> {
+        self.stats_at(Utc::now().timestamp())
+    }
+
+    /// Same as `stats`, frecencies being computed at `now`
+    fn stats_at(&self, now: i64) -> Option<String> {
+        let aliases = self.tagged(&[], Sort::Frecency);
+        if aliases.is_empty() {
+            None
+        } else {
+            let visits = self.visits().unwrap_or_default();
+            let color_alias = self.get_colors("alias").unwrap_or(Color::Yellow);
+            let color_separator = self.get_colors("separator").unwrap_or(Color::BrightCyan);
+
+            let mut res = Self::banner("STATISTICS");
+            for (alias, _) in aliases.iter() {
+                let stats = match visits.get(alias) {
+                    Some(visit) => format!(
+                        "{:>5} visits, last {}, frecency {}",
+                        visit.count,
+                        Local
+                            .timestamp_opt(visit.last_access, 0)
+                            .single()
+                            .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+                            .unwrap_or_default(),
+                        visit.frecency(now)
+                    ),
+                    None => format!("{:>5} visits", 0),
+                };
+                res.push_str(
+                    format!(
+                        "{:<12} {:<2} {}\n",
+                        alias.color(color_alias).bold(),
+                        "=>".color(color_separator).bold(),
+                        stats
+                    )
+                    .as_str(),
+                );
+            }
+            Some(res)
+        }
+    }
+
+    /// Get path of the file holding visits of aliases
+    fn visits_file(&self) -> PathBuf {
+        self.aliases_file.with_file_name("rualdi-visits.toml")
+    }
+
     /// Get rualdi configuration path with rualdi configuration
     /// file name concatenate
-    fn get_path<P: AsRef<Path>>(aliases_dir: P) -> PathBuf {
+    pub fn get_path<P: AsRef<Path>>(aliases_dir: P) -> PathBuf {
         aliases_dir.as_ref().join("rualdi.toml")
     }
 
+    /// Take a shared advisory lock on the lock file of the aliases
+    /// directory, blocking while another process writes the aliases
+    fn lock<P: AsRef<Path>>(aliases_dir: P) -> Result<fs::File> {
+        let path = aliases_dir.as_ref().join(".rualdi.lock");
+        let lock = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(Error::io(format!(
+                "could not create lock file: '{}'",
+                path.display()
+            )))?;
+        lock.lock_shared().map_err(Error::io(format!(
+            "could not lock alias file: '{}'",
+            path.display()
+        )))?;
+        Ok(lock)
+    }
+
+    /// Run f with lock upgraded to an exclusive lock, the shared lock is
+    /// restored afterwards. Without lock, f is run as is.
+    fn exclusive<T>(
+        lock: Option<&fs::File>,
+        path: &Path,
+        f: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        let lock = match lock {
+            Some(lock) => lock,
+            None => return f(),
+        };
+        let context = format!("could not lock alias file: '{}'", path.display());
+        lock.lock_exclusive().map_err(Error::io(&context))?;
+        let res = f();
+        lock.lock_shared().map_err(Error::io(&context))?;
+        res
+    }
+
+    /// Write the alias file with [`write_atomic`]
+    fn write_atomic(path: &Path, content: &str) -> Result<()> {
+        let context = format!("could not save alias file: '{}'", path.display());
+        write_atomic(path, content.as_bytes()).map_err(Error::io(context))
+    }
+
     /// Helper function to prevent having  to type match statement
-    fn selfmatch(
-        &mut self,
-        matching: Option<BTreeMap<String, String>>,
-    ) -> BTreeMap<String, String> {
+    fn selfmatch<V>(&mut self, matching: Option<BTreeMap<String, V>>) -> BTreeMap<String, V> {
         match matching {
             Some(matching) => matching,
             _ => {
@@ -647,11 +1307,9 @@ path = "magenta"
         if let Some(colors) = &self.colors {
             if colors.is_empty() {
                 None
-            } else if let Some(color) = colors.get(colored) {
-                // Unwraps to white if invalid
-                Some(Color::from(color.as_str()))
             } else {
-                None
+                // Unwraps to white if invalid
+                colors.get(colored).map(|color| Color::from(color.as_str()))
             }
         } else {
             None
@@ -659,46 +1317,661 @@ path = "magenta"
     }
 
     /// Search alias in rualdi aliases configuration file,
-    /// return None if alias not found
-    pub fn get(&self, alias: &str) -> Option<String> {
-        if let Some(aliases) = &self.aliases {
-            if aliases.is_empty() {
-                None
-            } else if let Some(path) = aliases.get(alias) {
-                Some(tilde(path).to_string())
-            } else {
-                None
-            }
-        } else {
-            None
+    /// return None if alias not found. A path starting with
+    /// `@other` is expanded with the path of the `other` alias,
+    /// raise an error if references are broken or cyclic.
+    /// Non unicode sequences of the path are replaced, see
+    /// [`get_raw`](Self::get_raw) to get it as is.
+    pub fn get(&self, alias: &str) -> Result<Option<String>> {
+        Ok(self
+            .get_bytes(alias)?
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    /// Search alias in rualdi aliases configuration file as
+    /// [`get`](Self::get) does, paths which are not valid UTF-8 are
+    /// returned as is.
+    pub fn get_raw(&self, alias: &str) -> Result<Option<PathBuf>> {
+        Ok(self.get_bytes(alias)?.map(encoding::path_from_bytes))
+    }
+
+    fn get_bytes(&self, alias: &str) -> Result<Option<Vec<u8>>> {
+        match &self.aliases {
+            Some(aliases) => Ok(expand_alias(aliases, alias, &mut Vec::new())?
+                .map(|path| encoding::unescape(&path))),
+            None => Ok(None),
         }
     }
 
-    /// Search environment variable associated to alias in
-    /// rualdi aliases configuration file,
-    /// return None if variable not found
-    pub fn get_env(&self, alias: &str) -> Result<String> {
-        if let Some(vars) = &self.vars {
-            if vars.is_empty() {
-                Err(anyhow!(format!("no variable for alias '{}'", alias)))
-            } else if let Some(var) = vars.get(alias) {
-                Ok(var.to_owned())
-            } else {
-                Err(anyhow!(format!("no variable for alias '{}'", alias)))
+    /// Search the alias matched by name. An exact match always wins, then
+    /// depending on `matching` the alias starting with name or containing
+    /// its letters in order is selected. Raise an error listing the
+    /// candidates if several aliases match.
+    pub fn find(&self, name: &str, matching: Matching) -> Result<Option<String>> {
+        let aliases = match &self.aliases {
+            Some(aliases) => aliases,
+            None => return Ok(None),
+        };
+        if aliases.contains_key(name) || matching == Matching::Exact {
+            return Ok(aliases
+                .get_key_value(name)
+                .map(|(alias, _)| alias.to_owned()));
+        }
+
+        let mut candidates: Vec<&String> = aliases
+            .keys()
+            .filter(|alias| alias.starts_with(name))
+            .collect();
+        if candidates.is_empty() && matching == Matching::Fuzzy {
+            candidates = aliases
+                .keys()
+                .filter(|alias| is_subsequence(name, alias))
+                .collect();
+        }
+
+        match candidates.as_slice() {
+            [] => Ok(None),
+            [alias] => Ok(Some(alias.to_string())),
+            _ => Err(Error::AmbiguousAlias {
+                name: name.into(),
+                candidates: candidates.into_iter().cloned().collect(),
+            }),
+        }
+    }
+
+    /// Search the aliases covering `path`, along with the part of `path`
+    /// under each of them. The alias with the longest path comes first.
+    /// Aliases which cannot be expanded or are not absolute are skipped.
+    /// Alias paths have their symlinks resolved if
+    /// [`Aliases::set_physical_paths`] is used, as `path` must then have.
+    pub fn which<P: AsRef<Path>>(&self, path: P) -> Vec<(String, PathBuf)> {
+        let path = path.as_ref();
+        let aliases = match &self.aliases {
+            Some(aliases) => aliases,
+            None => return Vec::new(),
+        };
+        let mut matches = aliases
+            .keys()
+            .filter_map(|alias| {
+                let alias_path = self.get_raw(alias).ok().flatten()?;
+                if !alias_path.is_absolute() {
+                    return None;
+                }
+                let alias_path = if self.physical_paths {
+                    fs::canonicalize(&alias_path).unwrap_or(alias_path)
+                } else {
+                    alias_path
+                };
+                let suffix = path.strip_prefix(&alias_path).ok()?.to_path_buf();
+                Some((alias_path.components().count(), alias.to_owned(), suffix))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by(|(a_len, a, _), (b_len, b, _)| b_len.cmp(a_len).then(a.cmp(b)));
+        matches
+            .into_iter()
+            .map(|(_, alias, suffix)| (alias, suffix))
+            .collect()
+    }
+
+    /// Abbreviate `path` with the alias or `[alias_hash]` mapping covering
+    /// the longest part of it, as `@api/src` or `%XDG_CONFIG_HOME/nvim`.
+    /// Aliases win over mappings of the same length, `path` is kept as is
+    /// when nothing covers it.
+    pub fn abbrev<P: AsRef<Path>>(&self, path: P) -> String {
+        let path = path.as_ref();
+        let mut best = self.which(path).into_iter().next().map(|(alias, suffix)| {
+            let len = path.components().count() - suffix.components().count();
+            (len, format!("@{}", alias), suffix)
+        });
+        for (hash_path, short) in self.build_alias_hash().unwrap_or_default() {
+            let hash_path = Path::new(&hash_path);
+            if let Ok(suffix) = path.strip_prefix(hash_path) {
+                let len = hash_path.components().count();
+                if best.as_ref().is_none_or(|(best_len, ..)| len > *best_len) {
+                    best = Some((len, short, suffix.to_path_buf()));
+                }
             }
-        } else {
-            Err(anyhow!(format!("no variable for alias '{}'", alias)))
+        }
+        match best {
+            Some((_, short, suffix)) if suffix.as_os_str().is_empty() => short,
+            Some((_, short, suffix)) => format!("{}/{}", short, suffix.display()),
+            None => path.display().to_string(),
         }
     }
-}
 
-impl Drop for Aliases {
-    fn drop(&mut self) {
-        if let Err(e) = self.save() {
-            eprintln!("{:#}", e);
+    /// Whether alias is defined in rualdi aliases configuration file
+    pub fn contains(&self, alias: &str) -> bool {
+        self.aliases
+            .as_ref()
+            .is_some_and(|aliases| aliases.contains_key(alias))
+    }
+
+    /// Search environment variables associated to alias in
+    /// rualdi aliases configuration file, subpaths of alias included,
+    /// raise an error if alias has no variable
+    pub fn get_env(&self, alias: &str) -> Result<Vec<String>> {
+        let vars = self
+            .vars
+            .as_ref()
+            .map(|vars| owned_vars(vars, alias))
+            .unwrap_or_default();
+        if vars.is_empty() {
+            Err(Error::EnvVarNotFound(alias.into()))
+        } else {
+            Ok(vars)
         }
     }
-}
+
+    /// Get the entry of an environment variable, None if variable not found
+    pub fn get_env_entry(&self, var: &str) -> Option<&EnvEntry> {
+        self.vars.as_ref().and_then(|vars| vars.get(var))
+    }
+
+    /// Get the escaped path an environment variable entry points on,
+    /// `None` if its alias not exists
+    pub fn get_env_path(&self, entry: &EnvEntry) -> Result<Option<PathBuf>> {
+        let path = self.get_raw(entry.alias())?;
+        Ok(match entry.subpath() {
+            Some(sub) => path.map(|path| path.join(sub)),
+            None => path,
+        })
+    }
+
+    /// Compare paths with their symlinks resolved in `which` and `abbrev`,
+    /// for paths given as `pwd -P` prints them
+    pub fn set_physical_paths(&mut self, physical: bool) {
+        self.physical_paths = physical;
+    }
+
+    /// Set the prefix environment variables of aliases are exported with,
+    /// variable names are checked once prefixed
+    pub fn set_env_prefix<S: Into<String>>(&mut self, prefix: S) {
+        self.env_prefix = prefix.into();
+    }
+
+    /// Set the case of prefixed variable names, upper case by default
+    pub fn set_env_case(&mut self, case: EnvCase) {
+        self.env_case = case;
+    }
+
+    /// Get the name var is exported as: cased and prefixed if `prefixed`,
+    /// as is otherwise
+    pub fn env_name(&self, var: &str, prefixed: bool) -> String {
+        if prefixed {
+            format!("{}{}", self.env_prefix, self.env_case.apply(var))
+        } else {
+            var.to_owned()
+        }
+    }
+
+    /// Check var can be exported, once named by `env_name`, with the rules
+    /// of `check_env_name`. The error suggests a valid name.
+    pub fn check_env_var(&self, var: &str, prefixed: bool) -> Result<()> {
+        let name = self.env_name(var, prefixed);
+        let reason = match env_name_error(&name) {
+            _ if var.is_empty() => "variable name cannot be empty",
+            Some(reason) => reason,
+            None => return Ok(()),
+        };
+        let reason = match self.suggest_env_var(var, prefixed) {
+            Some(suggestion) => format!("{}, try '{}'", reason, suggestion),
+            None => reason.to_owned(),
+        };
+        Err(Error::InvalidName { name, reason })
+    }
+
+    /// Check name can be exported as is: it must be a POSIX identifier which
+    /// does not override a critical variable such as `PATH`, nor rualdi
+    /// configuration
+    pub fn check_env_name(name: &str) -> Result<()> {
+        match env_name_error(name) {
+            Some(reason) => Err(Error::InvalidName {
+                name: name.to_owned(),
+                reason: reason.to_owned(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Get a valid variable name close to var: invalid characters are replaced
+    /// by '_' and names clashing with other variables get a `_DIR` suffix
+    fn suggest_env_var(&self, var: &str, prefixed: bool) -> Option<String> {
+        let mut suggestion = var
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        if suggestion.is_empty() {
+            return None;
+        }
+        if self
+            .env_name(&suggestion, prefixed)
+            .starts_with(|c: char| c.is_ascii_digit())
+        {
+            suggestion.insert(0, '_');
+        }
+        let name = self.env_name(&suggestion, prefixed);
+        if is_rualdi_var(&name) {
+            suggestion = suggestion.trim_start_matches('_').to_owned();
+        } else if is_critical_var(&name) {
+            suggestion.push_str("_DIR");
+        }
+        Some(suggestion)
+    }
+}
+
+/// Write content in a temporary file next to `path` and rename it over
+/// `path`, so that readers never see a partial file. A symlinked file is
+/// followed so the link is kept, and the permissions of an existing file
+/// are preserved.
+pub fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+
+    let mut tmp = NamedTempFile::new_in(dir)?;
+    tmp.write_all(content)?;
+    if let Ok(metadata) = fs::metadata(&path) {
+        fs::set_permissions(tmp.path(), metadata.permissions())?;
+    }
+    tmp.as_file().sync_all()?;
+    tmp.persist(&path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Get the escaped path of alias, following `@other/sub` references to
+/// other aliases. `chain` holds the aliases being expanded to detect cycles.
+/// The first existing candidate path is used, or the first one which
+/// can be expanded if none exists.
+fn expand_alias(
+    aliases: &BTreeMap<String, AliasEntry>,
+    alias: &str,
+    chain: &mut Vec<String>,
+) -> Result<Option<String>> {
+    let entry = match aliases.get(alias) {
+        Some(entry) => entry,
+        None => return Ok(None),
+    };
+    if chain.iter().any(|a| a == alias) {
+        chain.push(alias.into());
+        return Err(Error::AliasCycle(chain.to_owned()));
+    }
+    chain.push(alias.into());
+
+    let depth = chain.len();
+    let mut fallback: Option<Result<String>> = None;
+    for path in entry.escaped_paths() {
+        let expanded = expand_path(aliases, alias, &path, chain);
+        // A single path is used whether it exists or not
+        if entry.alternatives.is_empty() {
+            return expanded.map(Some);
+        }
+        chain.truncate(depth);
+        match expanded {
+            Ok(expanded) if encoding::path_from_bytes(encoding::unescape(&expanded)).exists() => {
+                return Ok(Some(expanded))
+            }
+            // An expanded candidate wins over the errors of previous ones
+            Ok(expanded) if fallback.as_ref().is_none_or(Result::is_err) => {
+                fallback = Some(Ok(expanded))
+            }
+            Ok(_) => {}
+            Err(e) => {
+                fallback.get_or_insert(Err(e));
+            }
+        }
+    }
+    fallback.unwrap().map(Some)
+}
+
+/// Expand one escaped candidate path of alias
+fn expand_path(
+    aliases: &BTreeMap<String, AliasEntry>,
+    alias: &str,
+    path: &str,
+    chain: &mut Vec<String>,
+) -> Result<String> {
+    match path.strip_prefix('@') {
+        Some(reference) => {
+            let (name, sub) = match reference.find('/') {
+                Some(index) => (&reference[..index], &reference[index + 1..]),
+                None => (reference, ""),
+            };
+            match expand_alias(aliases, name, chain)? {
+                Some(base) if sub.is_empty() => Ok(base),
+                Some(base) => Ok(format!(
+                    "{}/{}",
+                    base.trim_end_matches('/'),
+                    expand_vars(alias, sub)?
+                )),
+                None => Err(Error::BrokenReference {
+                    alias: alias.into(),
+                    reference: name.into(),
+                }),
+            }
+        }
+        None => expand_vars(alias, path),
+    }
+}
+
+/// Expand tilde and environment variables in the escaped path of alias,
+/// their values are escaped as well. `$$` stands for a literal `$`.
+fn expand_vars(alias: &str, path: &str) -> Result<String> {
+    let home_dir = || dirs::home_dir().map(|home| escape_path(&home));
+    let context = |var: &str| {
+        env::var_os(var)
+            .map(|value| Some(escape_path(Path::new(&value))))
+            .ok_or(env::VarError::NotPresent)
+    };
+    let mut res = String::with_capacity(path.len());
+    for (i, part) in path.split("$$").enumerate() {
+        let expanded = if i == 0 {
+            full_with_context(part, home_dir, context)
+        } else {
+            res.push('$');
+            env_with_context(part, context)
+        };
+        res.push_str(&expanded.map_err(|e| Error::UnsetVariable {
+            alias: alias.into(),
+            var: e.var_name,
+            source: e.cause,
+        })?);
+    }
+    Ok(res)
+}
+
+fn escape_path(path: &Path) -> String {
+    encoding::escape_bytes(&encoding::path_to_bytes(path))
+}
+
+/// Check that the references of alias to other aliases can be followed,
+/// variables are only expanded when resolving so they may be unset here
+fn check_references(aliases: &BTreeMap<String, AliasEntry>, alias: &str) -> Result<()> {
+    match expand_alias(aliases, alias, &mut Vec::new()) {
+        Ok(_) | Err(Error::UnsetVariable { .. }) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Whether the letters of `name` appear in order in `alias`, ignoring case
+fn is_subsequence(name: &str, alias: &str) -> bool {
+    let mut letters = alias.chars().flat_map(char::to_lowercase);
+    name.chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| letters.any(|l| l == c))
+}
+
+/// Get the variables pointing on alias or on one of its subpaths
+fn owned_vars(vars: &BTreeMap<String, EnvEntry>, alias: &str) -> Vec<String> {
+    vars.iter()
+        .filter(|(_, entry)| entry.alias() == alias)
+        .map(|(var, _)| var.to_owned())
+        .collect()
+}
+
+fn default_env_prefix() -> String {
+    DEFAULT_ENV_PREFIX.into()
+}
+
+fn is_critical_var(name: &str) -> bool {
+    CRITICAL_VARS
+        .iter()
+        .any(|critical| critical.eq_ignore_ascii_case(name))
+}
+
+/// Get why name cannot be exported, if it cannot
+fn env_name_error(name: &str) -> Option<&'static str> {
+    if name.is_empty() {
+        Some("variable name cannot be empty")
+    } else if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Some("variable name can only contain ASCII letters, digits and '_'")
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        Some("variable name cannot start with a digit")
+    } else if is_critical_var(name) {
+        Some("variable would override a critical variable")
+    } else if is_rualdi_var(name) {
+        Some("variable would override rualdi configuration")
+    } else {
+        None
+    }
+}
+
+/// Whether name is one of the `_RAD_` variables configuring rualdi,
+/// in any case as shells may not tell them apart
+fn is_rualdi_var(name: &str) -> bool {
+    name.get(..5)
+        .is_some_and(|start| start.eq_ignore_ascii_case("_RAD_"))
+}
+
+/// Check an alias name can be used as the first component of a path
+fn validate_alias(alias: &str) -> Result<()> {
+    let reason = if alias.is_empty() {
+        "alias cannot be empty"
+    } else if alias.contains('/') {
+        "alias cannot contain '/'"
+    } else if alias == "." || alias == ".." {
+        "alias cannot be a relative directory"
+    } else if alias.starts_with('-') {
+        "alias cannot start with '-'"
+    } else {
+        return Ok(());
+    };
+    Err(Error::InvalidName {
+        name: alias.into(),
+        reason: reason.into(),
+    })
+}
+
+/// Upgrade document from version to [`CONFIG_VERSION`]
+fn migrate(document: &mut Document, version: i64) {
+    for migration in &MIGRATIONS[version.max(0) as usize..] {
+        migration(document);
+    }
+    set_version(document);
+}
+
+/// Write the current version at the top of the document, comments heading
+/// the file stay above it
+fn set_version(document: &mut Document) {
+    let root = document.as_table_mut();
+    let header = if root.contains_key("version") || root.iter().any(|(_, item)| item.is_value()) {
+        None
+    } else {
+        root.iter_mut()
+            .filter_map(|(_, item)| item.as_table_mut())
+            .min_by_key(|table| table.position().unwrap_or(usize::MAX))
+            .and_then(|table| {
+                let prefix = table.decor().prefix()?.as_str()?.to_owned();
+                table.decor_mut().set_prefix("\n");
+                Some(prefix)
+            })
+    };
+
+    // Replacing the value keeps the comments above an existing version key
+    match root.get_mut("version") {
+        Some(version) => *version = toml_edit::value(CONFIG_VERSION as i64),
+        None => {
+            root.insert("version", toml_edit::value(CONFIG_VERSION as i64));
+        }
+    }
+    if let (Some(header), Some(decor)) = (header, root.key_decor_mut("version")) {
+        decor.set_prefix(header);
+    }
+}
+
+/// Version 0 to 1: the `[alias_map]` section documented by older READMEs
+/// is renamed to `[alias_hash]`, entries already in `[alias_hash]` win
+fn migrate_alias_map(document: &mut Document) {
+    let alias_map = match document.remove("alias_map") {
+        Some(Item::Table(alias_map)) => alias_map,
+        Some(item) => {
+            document.insert("alias_map", item);
+            return;
+        }
+        None => return,
+    };
+
+    match document.get_mut("alias_hash") {
+        Some(Item::Table(alias_hash)) => {
+            for (key, item) in alias_map.into_iter() {
+                if !alias_hash.contains_key(&key) {
+                    alias_hash.insert(&key, item);
+                }
+            }
+        }
+        _ => {
+            document.insert("alias_hash", Item::Table(alias_map));
+        }
+    }
+}
+
+/// Migration of a version adding a new form of entries, documents
+/// of the previous version are read as they are
+fn unchanged(_document: &mut Document) {}
+
+/// Version 5 to 6: `[environment]` maps variables to aliases instead of
+/// aliases to variables, so that an alias can have several variables
+fn migrate_environment(document: &mut Document) {
+    let environment = match document
+        .get_mut("environment")
+        .and_then(|item| item.as_table_like_mut())
+    {
+        Some(environment) => environment,
+        None => return,
+    };
+
+    let entries: Vec<(String, String)> = environment
+        .iter()
+        .filter_map(|(alias, item)| Some((alias.to_owned(), item.as_str()?.to_owned())))
+        .collect();
+    for (alias, _) in entries.iter() {
+        environment.remove(alias);
+    }
+    for (alias, var) in entries {
+        if !environment.contains_key(&var) {
+            environment.insert(&var, toml_edit::value(alias));
+        }
+    }
+}
+
+/// Apply the differences between `original` and `updated` to `document`.
+/// Keys whose value did not change are left untouched, changed values keep
+/// their surrounding comments and removed keys are dropped. Unknown top level
+/// keys are never removed, new top level tables are created as `[table]`
+/// sections and nested tables as inline tables.
+fn merge_table(
+    document: &mut toml_edit::Table,
+    original: &toml::value::Table,
+    updated: &toml::value::Table,
+    top_level: bool,
+) {
+    let empty = toml::value::Table::new();
+
+    for (key, value) in updated {
+        if original.get(key) == Some(value) && document.contains_key(key) {
+            continue;
+        }
+        // Sections defaulted to empty ones when loading are not written
+        if value.as_table().is_some_and(|table| table.is_empty()) && !document.contains_key(key) {
+            continue;
+        }
+
+        match (value, document.get_mut(key)) {
+            (toml::Value::Table(table), Some(Item::Table(doc_table))) => {
+                let original = original
+                    .get(key)
+                    .and_then(toml::Value::as_table)
+                    .unwrap_or(&empty);
+                merge_table(doc_table, original, table, false);
+            }
+            (toml::Value::Table(table), None) if top_level => {
+                let mut doc_table = toml_edit::Table::new();
+                merge_table(&mut doc_table, &empty, table, false);
+                document.insert(key, Item::Table(doc_table));
+            }
+            (value, Some(Item::Value(doc_value))) => {
+                let decor = doc_value.decor().clone();
+                *doc_value = to_edit_value(value);
+                *doc_value.decor_mut() = decor;
+            }
+            (value, _) => {
+                document.insert(key, toml_edit::value(to_edit_value(value)));
+            }
+        }
+    }
+
+    if !top_level {
+        for key in original.keys() {
+            if !updated.contains_key(key) {
+                document.remove(key);
+            }
+        }
+    }
+}
+
+/// Convert a `toml` value to its `toml_edit` equivalent,
+/// tables are converted to inline tables
+fn to_edit_value(value: &toml::Value) -> toml_edit::Value {
+    match value {
+        toml::Value::String(s) => s.as_str().into(),
+        toml::Value::Integer(i) => (*i).into(),
+        toml::Value::Float(f) => (*f).into(),
+        toml::Value::Boolean(b) => (*b).into(),
+        toml::Value::Datetime(d) => d
+            .to_string()
+            .parse::<toml_edit::Datetime>()
+            .map(toml_edit::Value::from)
+            .unwrap_or_else(|_| d.to_string().into()),
+        toml::Value::Array(array) => array
+            .iter()
+            .map(to_edit_value)
+            .collect::<toml_edit::Array>()
+            .into(),
+        toml::Value::Table(table) => table
+            .iter()
+            .map(|(k, v)| (k.as_str(), to_edit_value(v)))
+            .collect::<toml_edit::InlineTable>()
+            .into(),
+    }
+}
+
+/// `toml::Value::try_from` encodes datetimes as a table holding a single
+/// private key, turn them back into datetimes
+fn restore_datetimes(value: &mut toml::Value) {
+    const DATETIME_KEY: &str = "$__toml_private_datetime";
+
+    let datetime = match value {
+        toml::Value::Table(table) if table.len() == 1 => table
+            .get(DATETIME_KEY)
+            .and_then(toml::Value::as_str)
+            .and_then(|d| d.parse::<toml::value::Datetime>().ok()),
+        _ => None,
+    };
+    if let Some(datetime) = datetime {
+        *value = toml::Value::Datetime(datetime);
+        return;
+    }
+    match value {
+        toml::Value::Table(table) => table.iter_mut().for_each(|(_, v)| restore_datetimes(v)),
+        toml::Value::Array(array) => array.iter_mut().for_each(restore_datetimes),
+        _ => (),
+    }
+}
+
+impl Drop for Aliases {
+    fn drop(&mut self) {
+        if let Err(e) = self.save() {
+            eprint!("{}", e);
+            let mut source = std::error::Error::source(&e);
+            while let Some(cause) = source {
+                eprint!(": {}", cause);
+                source = cause.source();
+            }
+            eprintln!();
+        }
+    }
+}
 
 #[cfg(test)]
 pub struct MockAliases;
@@ -706,7 +1979,7 @@ pub struct MockAliases;
 #[cfg(test)]
 impl MockAliases {
     pub fn open() -> Aliases {
-        let mut aliases: BTreeMap<String, String> = BTreeMap::new();
+        let mut aliases: BTreeMap<String, AliasEntry> = BTreeMap::new();
         aliases.insert("test".into(), "/test/haha".into());
         aliases.insert("Home".into(), "~".into());
 
@@ -716,22 +1989,32 @@ impl MockAliases {
         colors.insert("path".into(), "green".into());
 
         Aliases {
+            version: CONFIG_VERSION,
             aliases: Some(aliases),
             vars: None,
             colors: Some(colors),
             alias_hash: None,
+            hosts: None,
+            origins: Default::default(),
+            env_prefix: DEFAULT_ENV_PREFIX.into(),
+            env_case: EnvCase::Upper,
+            physical_paths: false,
+            read_only: false,
             modified: false,
+            moved_visits: Vec::new(),
             aliases_file: PathBuf::new(),
+            lock: None,
+            document: Document::new(),
         }
     }
 
     pub fn open_with_env() -> Aliases {
-        let mut aliases: BTreeMap<String, String> = BTreeMap::new();
+        let mut aliases: BTreeMap<String, AliasEntry> = BTreeMap::new();
         aliases.insert("test".into(), "/test/haha".into());
         aliases.insert("Home".into(), "~".into());
 
-        let mut vars: BTreeMap<String, String> = BTreeMap::new();
-        vars.insert("test".into(), "TEST".into());
+        let mut vars: BTreeMap<String, EnvEntry> = BTreeMap::new();
+        vars.insert("TEST".into(), "test".into());
 
         let mut colors: BTreeMap<String, String> = BTreeMap::new();
         colors.insert("name".into(), "red".into());
@@ -739,24 +2022,34 @@ impl MockAliases {
         colors.insert("path".into(), "green".into());
 
         Aliases {
+            version: CONFIG_VERSION,
             aliases: Some(aliases),
             vars: Some(vars),
             colors: Some(colors),
             alias_hash: None,
+            hosts: None,
+            origins: Default::default(),
+            env_prefix: DEFAULT_ENV_PREFIX.into(),
+            env_case: EnvCase::Upper,
+            physical_paths: false,
+            read_only: false,
             modified: false,
+            moved_visits: Vec::new(),
             aliases_file: PathBuf::new(),
+            lock: None,
+            document: Document::new(),
         }
     }
 
     pub fn open_with_vars() -> Aliases {
-        let mut aliases: BTreeMap<String, String> = BTreeMap::new();
+        let mut aliases: BTreeMap<String, AliasEntry> = BTreeMap::new();
         aliases.insert("test".into(), "/test/haha".into());
         aliases.insert("test2".into(), "/test2/haha".into());
         aliases.insert("Home".into(), "~".into());
 
-        let mut vars: BTreeMap<String, String> = BTreeMap::new();
-        vars.insert("test".into(), "TEST".into());
-        vars.insert("test2".into(), "TEST2".into());
+        let mut vars: BTreeMap<String, EnvEntry> = BTreeMap::new();
+        vars.insert("TEST".into(), "test".into());
+        vars.insert("TEST2".into(), "test2".into());
 
         let mut colors: BTreeMap<String, String> = BTreeMap::new();
         colors.insert("name".into(), "red".into());
@@ -764,18 +2057,28 @@ impl MockAliases {
         colors.insert("path".into(), "green".into());
 
         Aliases {
+            version: CONFIG_VERSION,
             aliases: Some(aliases),
             vars: Some(vars),
             colors: Some(colors),
             alias_hash: None,
+            hosts: None,
+            origins: Default::default(),
+            env_prefix: DEFAULT_ENV_PREFIX.into(),
+            env_case: EnvCase::Upper,
+            physical_paths: false,
+            read_only: false,
             modified: false,
+            moved_visits: Vec::new(),
             aliases_file: PathBuf::new(),
+            lock: None,
+            document: Document::new(),
         }
     }
 
     pub fn open_no_aliases() -> Aliases {
-        let aliases: BTreeMap<String, String> = BTreeMap::new();
-        let vars: BTreeMap<String, String> = BTreeMap::new();
+        let aliases: BTreeMap<String, AliasEntry> = BTreeMap::new();
+        let vars: BTreeMap<String, EnvEntry> = BTreeMap::new();
 
         let mut colors: BTreeMap<String, String> = BTreeMap::new();
         colors.insert("name".into(), "red".into());
@@ -783,43 +2086,73 @@ impl MockAliases {
         colors.insert("path".into(), "green".into());
 
         Aliases {
+            version: CONFIG_VERSION,
             aliases: Some(aliases),
             vars: Some(vars),
             colors: Some(colors),
             alias_hash: None,
+            hosts: None,
+            origins: Default::default(),
+            env_prefix: DEFAULT_ENV_PREFIX.into(),
+            env_case: EnvCase::Upper,
+            physical_paths: false,
+            read_only: false,
             modified: false,
+            moved_visits: Vec::new(),
             aliases_file: PathBuf::new(),
+            lock: None,
+            document: Document::new(),
         }
     }
 
     pub fn open_no_colors() -> Aliases {
-        let mut aliases: BTreeMap<String, String> = BTreeMap::new();
+        let mut aliases: BTreeMap<String, AliasEntry> = BTreeMap::new();
         aliases.insert("test".into(), "/test/haha".into());
         aliases.insert("test2".into(), "/test2/haha".into());
         aliases.insert("Home".into(), "~".into());
 
-        let mut vars: BTreeMap<String, String> = BTreeMap::new();
-        vars.insert("test".into(), "TEST".into());
-        vars.insert("test2".into(), "TEST2".into());
+        let mut vars: BTreeMap<String, EnvEntry> = BTreeMap::new();
+        vars.insert("TEST".into(), "test".into());
+        vars.insert("TEST2".into(), "test2".into());
 
         Aliases {
+            version: CONFIG_VERSION,
             aliases: Some(aliases),
             vars: Some(vars),
             colors: None,
             alias_hash: None,
+            hosts: None,
+            origins: Default::default(),
+            env_prefix: DEFAULT_ENV_PREFIX.into(),
+            env_case: EnvCase::Upper,
+            physical_paths: false,
+            read_only: false,
             modified: false,
+            moved_visits: Vec::new(),
             aliases_file: PathBuf::new(),
+            lock: None,
+            document: Document::new(),
         }
     }
 
     pub fn open_empty() -> Aliases {
         Aliases {
+            version: CONFIG_VERSION,
             aliases: None,
             vars: None,
             colors: None,
             alias_hash: None,
+            hosts: None,
+            origins: Default::default(),
+            env_prefix: DEFAULT_ENV_PREFIX.into(),
+            env_case: EnvCase::Upper,
+            physical_paths: false,
+            read_only: false,
             modified: false,
+            moved_visits: Vec::new(),
             aliases_file: PathBuf::new(),
+            lock: None,
+            document: Document::new(),
         }
     }
 }
@@ -832,20 +2165,20 @@ pub struct TmpConfig {
 
 #[cfg(test)]
 impl TmpConfig {
-    pub fn create_dir() -> Result<Self> {
+    pub fn create_dir() -> anyhow::Result<Self> {
         let tmp_dir = Builder::new().prefix("test_rualdi").tempdir()?;
         let file_path = tmp_dir.path().join(".default");
         let tmp_file = File::create(file_path)?;
         Ok(TmpConfig { tmp_dir, tmp_file })
     }
 
-    pub fn with_empty(mut self) -> Result<Self> {
+    pub fn with_empty(mut self) -> anyhow::Result<Self> {
         let file_path = self.tmp_dir.path().join("rualdi.toml");
         self.tmp_file = File::create(file_path)?;
         Ok(self)
     }
 
-    pub fn with_base(mut self) -> Result<Self> {
+    pub fn with_base(mut self) -> anyhow::Result<Self> {
         let file_path = self.tmp_dir.path().join("rualdi.toml");
         self.tmp_file = File::create(file_path)?;
         writeln!(
@@ -855,7 +2188,7 @@ impl TmpConfig {
         Ok(self)
     }
 
-    pub fn with_content(mut self, toml: toml::value::Value) -> Result<Self> {
+    pub fn with_content(mut self, toml: toml::value::Value) -> anyhow::Result<Self> {
         self.tmp_file.write_all(toml.to_string().as_bytes())?;
         self.tmp_file.flush()?;
         Ok(self)
@@ -870,7 +2203,7 @@ mod tests_get {
     fn simple() {
         let alias = "test";
         let aliases = MockAliases::open();
-        let pointed = aliases.get(alias);
+        let pointed = aliases.get(alias).unwrap();
         assert_eq!(pointed, Some("/test/haha".into()));
     }
 
@@ -879,7 +2212,7 @@ mod tests_get {
         let alias = "Home";
         let home = std::env::var("HOME").unwrap();
         let aliases = MockAliases::open();
-        let pointed = aliases.get(alias);
+        let pointed = aliases.get(alias).unwrap();
         assert_eq!(pointed, Some(home));
     }
 
@@ -887,7 +2220,7 @@ mod tests_get {
     fn not_existing() {
         let alias = "should_fail";
         let aliases = MockAliases::open();
-        let pointed = aliases.get(alias);
+        let pointed = aliases.get(alias).unwrap();
         assert_eq!(pointed, None);
     }
 
@@ -895,7 +2228,7 @@ mod tests_get {
     fn from_no_aliases() {
         let alias = "should_fail";
         let aliases = MockAliases::open_no_aliases();
-        let pointed = aliases.get(alias);
+        let pointed = aliases.get(alias).unwrap();
         assert_eq!(pointed, None);
     }
 
@@ -903,272 +2236,1195 @@ mod tests_get {
     fn from_empty_aliases() {
         let alias = "should_fail";
         let aliases = MockAliases::open_empty();
-        let pointed = aliases.get(alias);
+        let pointed = aliases.get(alias).unwrap();
         assert_eq!(pointed, None);
     }
 }
 
 #[cfg(test)]
-mod tests_add {
+mod tests_get_vars {
     use super::*;
 
     #[test]
-    fn to_empty_aliases() {
-        let alias = String::from("test");
-        let path = String::from("/test");
-        let mut expected_aliases: BTreeMap<String, String> = BTreeMap::new();
-
-        expected_aliases.insert(alias.to_owned(), path.to_owned());
-
+    fn expanded() {
+        env::set_var("RUALDI_TEST_PROJECTS", "/projects");
+        env::set_var("RUALDI_TEST_VERSION", "v1");
         let mut aliases = MockAliases::open_no_aliases();
-        let res = aliases.add(alias, path);
-        assert!(res.is_ok());
-        assert_eq!(aliases.aliases, Some(expected_aliases));
+        let map = aliases.aliases.as_mut().unwrap();
+        map.insert("api".into(), "$RUALDI_TEST_PROJECTS/api".into());
+        map.insert("web".into(), "${RUALDI_TEST_PROJECTS}/web".into());
+        map.insert("v1".into(), "@api/$RUALDI_TEST_VERSION".into());
+        assert_eq!(aliases.get("api").unwrap(), Some("/projects/api".into()));
+        assert_eq!(aliases.get("web").unwrap(), Some("/projects/web".into()));
+        assert_eq!(aliases.get("v1").unwrap(), Some("/projects/api/v1".into()));
     }
 
     #[test]
-    fn to_none_aliases() {
-        let alias = String::from("test");
-        let path = String::from("/test");
-        let mut expected_aliases: BTreeMap<String, String> = BTreeMap::new();
-
-        expected_aliases.insert(alias.to_owned(), path.to_owned());
-
-        let mut aliases = MockAliases::open_empty();
-        let res = aliases.add(alias, path);
-        assert!(res.is_ok());
-        assert_eq!(aliases.aliases, Some(expected_aliases));
+    fn literal_dollar() {
+        env::set_var("RUALDI_TEST_PROJECTS", "/projects");
+        let mut aliases = MockAliases::open_no_aliases();
+        let map = aliases.aliases.as_mut().unwrap();
+        map.insert("price".into(), "/shop/$$5/$$$RUALDI_TEST_PROJECTS".into());
+        map.insert("tilde".into(), "/x/$$~".into());
+        assert_eq!(
+            aliases.get("price").unwrap(),
+            Some("/shop/$5/$/projects".into())
+        );
+        assert_eq!(aliases.get("tilde").unwrap(), Some("/x/$~".into()));
+        assert_eq!(
+            AliasEntry::from_path("/shop/$5").path,
+            "/shop/$$5".to_string()
+        );
     }
 
     #[test]
-    fn to_filled_aliases() {
-        let alias = String::from("test2");
-        let path = String::from("/test");
-        let mut expected_aliases: BTreeMap<String, String> = BTreeMap::new();
+    fn unset() {
+        let mut aliases = MockAliases::open_no_aliases();
+        aliases
+            .add("api".into(), "$RUALDI_TEST_UNSET/api".into())
+            .unwrap();
+        let res = aliases.get("api");
+        assert!(matches!(
+            &res,
+            Err(Error::UnsetVariable { alias, var, .. })
+                if alias == "api" && var == "RUALDI_TEST_UNSET"
+        ));
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "environment variable 'RUALDI_TEST_UNSET' used by alias 'api' is not set"
+        );
+    }
+}
 
-        expected_aliases.insert(alias.to_owned(), path.to_owned());
-        expected_aliases.insert("test".into(), "/test/haha".into());
-        expected_aliases.insert("Home".into(), "~".into());
+#[cfg(all(test, unix))]
+mod tests_get_raw {
+    use super::*;
 
-        let mut aliases = MockAliases::open();
-        let res = aliases.add(alias, path);
-        assert!(res.is_ok());
-        assert_eq!(aliases.aliases, Some(expected_aliases));
+    #[test]
+    fn not_unicode() {
+        env::set_var("RUALDI_TEST_BACKSLASH", "back\\slash");
+        let path = encoding::path_from_bytes(b"/data/caf\xe9".to_vec());
+        let mut aliases = MockAliases::open_no_aliases();
+        aliases
+            .add_entry("legacy".into(), AliasEntry::from_path(&path))
+            .unwrap();
+        aliases
+            .add("sub".into(), "@legacy/$RUALDI_TEST_BACKSLASH".into())
+            .unwrap();
+        assert_eq!(aliases.get_raw("legacy").unwrap(), Some(path.clone()));
+        assert_eq!(
+            aliases.get_raw("sub").unwrap(),
+            Some(path.join("back\\slash"))
+        );
+        assert_eq!(
+            aliases.get("legacy").unwrap(),
+            Some("/data/caf\u{fffd}".into())
+        );
     }
 
     #[test]
-    fn existing() {
-        let alias = String::from("test");
-        let path = String::from("/test");
+    fn update() {
         let mut aliases = MockAliases::open();
-        let res = aliases.add(alias, path);
-        assert!(res.is_err());
+        let path = encoding::path_from_bytes(b"/data/caf\xe9".to_vec());
+        let old = aliases
+            .update_entry("test".into(), AliasEntry::from_path(&path))
+            .unwrap();
+        assert_eq!(old, "/test/haha");
+        assert_eq!(aliases.get_raw("test").unwrap(), Some(path));
     }
 }
 
 #[cfg(test)]
-mod tests_remove {
+mod tests_get_reference {
     use super::*;
 
+    fn aliases() -> Aliases {
+        let mut aliases = MockAliases::open_no_aliases();
+        let map = aliases.aliases.as_mut().unwrap();
+        map.insert("company".into(), "/src/company/".into());
+        map.insert("api".into(), "@company/api".into());
+        map.insert("v1".into(), "@api/v1".into());
+        map.insert("root".into(), "@company".into());
+        map.insert("broken".into(), "@nope/x".into());
+        map.insert("a".into(), "@b".into());
+        map.insert("b".into(), "@c/sub".into());
+        map.insert("c".into(), "@a".into());
+        aliases
+    }
+
     #[test]
-    fn existing() {
-        let alias = String::from("test");
-        let mut aliases = MockAliases::open();
-        let res = aliases.remove(alias);
-        assert!(res.is_ok());
+    fn expanded() {
+        let aliases = aliases();
+        assert_eq!(aliases.get("api").unwrap(), Some("/src/company/api".into()));
+        assert_eq!(
+            aliases.get("v1").unwrap(),
+            Some("/src/company/api/v1".into())
+        );
+        assert_eq!(aliases.get("root").unwrap(), Some("/src/company/".into()));
     }
 
     #[test]
-    fn not_existing() {
-        let alias = String::from("not_exsting");
-        let mut aliases = MockAliases::open_no_aliases();
-        let res = aliases.remove(alias);
-        assert!(res.is_err());
+    fn broken() {
+        let res = aliases().get("broken");
+        assert!(matches!(
+            res,
+            Err(Error::BrokenReference { alias, reference }) if alias == "broken" && reference == "nope"
+        ));
     }
 
     #[test]
-    fn from_empty_aliases() {
-        let alias = String::from("not_exsting");
-        let mut aliases = MockAliases::open_empty();
-        let res = aliases.remove(alias);
-        assert!(res.is_err());
+    fn cycle() {
+        let res = aliases().get("a");
+        assert!(matches!(&res, Err(Error::AliasCycle(chain)) if chain == &["a", "b", "c", "a"]));
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "cyclic alias references: a -> b -> c -> a"
+        );
     }
 
     #[test]
-    fn from_none_aliases() {
-        let alias = String::from("not_exsting");
-        let mut aliases = MockAliases::open_no_aliases();
-        let res = aliases.remove(alias);
-        assert!(res.is_err());
+    fn add_broken() {
+        let mut aliases = aliases();
+        let res = aliases.add("new".into(), "@nope".into());
+        assert!(matches!(res, Err(Error::BrokenReference { .. })));
+        assert_eq!(aliases.get("new").unwrap(), None);
+    }
+
+    #[test]
+    fn update_cycle() {
+        let mut aliases = aliases();
+        let res = aliases.update("company".into(), "@v1".into());
+        assert!(matches!(res, Err(Error::AliasCycle(_))));
+        assert_eq!(
+            aliases.get("company").unwrap(),
+            Some("/src/company/".into())
+        );
     }
 }
 
 #[cfg(test)]
-mod tests_get_env {
+mod tests_get_candidates {
     use super::*;
 
-    #[test]
-    fn existing() {
-        let alias = "test";
-        let aliases = MockAliases::open_with_env();
-        let ret = aliases.get_env(alias);
-        assert!(ret.is_ok());
-        assert_eq!(ret.unwrap(), String::from("TEST"));
+    fn aliases(existing: &Path) -> Aliases {
+        let mut aliases = MockAliases::open_no_aliases();
+        let map = aliases.aliases.as_mut().unwrap();
+        let entry = AliasEntry {
+            alternatives: vec![
+                existing.to_str().unwrap().into(),
+                "/not/existing/either".into(),
+            ],
+            ..AliasEntry::new("/not/existing")
+        };
+        map.insert("x".into(), entry);
+        map.insert("sub".into(), "@x/sub".into());
+        aliases
     }
 
     #[test]
-    fn not_existing() {
-        let alias = "NOPE";
-        let aliases = MockAliases::open_with_env();
-        let ret = aliases.get_env(alias);
-        assert!(ret.is_err());
+    fn first_existing() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let aliases = aliases(dir.path());
+        let existing = dir.path().to_str().unwrap();
+        assert_eq!(aliases.get("x")?, Some(existing.into()));
+        assert_eq!(aliases.get("sub")?, Some(format!("{}/sub", existing)));
+        Ok(())
     }
 
     #[test]
-    fn from_no_env() {
-        let alias = "should_fail";
-        let aliases = MockAliases::open_no_aliases();
-        let ret = aliases.get_env(alias);
-        assert!(ret.is_err());
+    fn none_existing() {
+        let aliases = aliases(Path::new("/not/existing/at/all"));
+        assert_eq!(aliases.get("x").unwrap(), Some("/not/existing".into()));
     }
 
     #[test]
-    fn from_empty_env() {
-        let alias = "should_fail";
-        let aliases = MockAliases::open_empty();
-        let ret = aliases.get_env(alias);
-        assert!(ret.is_err());
+    fn first_expanded() {
+        let mut aliases = MockAliases::open_no_aliases();
+        let map = aliases.aliases.as_mut().unwrap();
+        let entry = AliasEntry {
+            alternatives: vec!["/not/existing".into(), "/not/existing/either".into()],
+            ..AliasEntry::new("$RUALDI_TEST_UNSET/x")
+        };
+        map.insert("x".into(), entry);
+        let entry = AliasEntry {
+            alternatives: vec!["@nope".into()],
+            ..AliasEntry::new("$RUALDI_TEST_UNSET/y")
+        };
+        map.insert("y".into(), entry);
+        assert_eq!(aliases.get("x").unwrap(), Some("/not/existing".into()));
+        assert!(matches!(
+            aliases.get("y"),
+            Err(Error::UnsetVariable { var, .. }) if var == "RUALDI_TEST_UNSET"
+        ));
     }
 }
 
 #[cfg(test)]
-mod tests_add_env {
+mod tests_which {
     use super::*;
 
     #[test]
-    fn not_existing_var() {
-        let mut aliases = MockAliases::open();
-        let alias = String::from("Home");
-        let var = String::from("MY_HOME");
-        let mut expected_vars: BTreeMap<String, String> = BTreeMap::new();
+    fn longest_first() {
+        let mut aliases = MockAliases::open_no_aliases();
+        let map = aliases.aliases.as_mut().unwrap();
+        map.insert("src".into(), "/home/user/src".into());
+        map.insert("api".into(), "@src/api".into());
+        map.insert("other".into(), "/home/user/src/apiv2".into());
+        map.insert("relative".into(), "src".into());
+        map.insert("broken".into(), "@nope".into());
+        assert_eq!(
+            aliases.which("/home/user/src/api/src/handlers"),
+            vec![
+                ("api".into(), PathBuf::from("src/handlers")),
+                ("src".into(), PathBuf::from("api/src/handlers")),
+            ]
+        );
+        assert_eq!(
+            aliases.which("/home/user/src"),
+            vec![("src".into(), PathBuf::new())]
+        );
+        assert!(aliases.which("/home/user").is_empty());
+    }
 
-        expected_vars.insert(alias.to_owned(), var.to_owned());
+    #[cfg(unix)]
+    #[test]
+    fn physical_paths() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let target = fs::canonicalize(dir.path())?.join("target");
+        fs::create_dir(&target)?;
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&target, &link)?;
 
-        let res = aliases.add_env(alias, var);
-        assert!(res.is_ok());
-        assert_eq!(aliases.vars, Some(expected_vars));
+        let mut aliases = MockAliases::open_no_aliases();
+        let map = aliases.aliases.as_mut().unwrap();
+        map.insert("link".into(), link.to_str().unwrap().into());
+        assert!(aliases.which(target.join("sub")).is_empty());
+        aliases.set_physical_paths(true);
+        assert_eq!(
+            aliases.which(target.join("sub")),
+            vec![("link".into(), PathBuf::from("sub"))]
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_abbrev {
+    use super::*;
+
+    fn aliases() -> Aliases {
+        let mut aliases = MockAliases::open_no_aliases();
+        let map = aliases.aliases.as_mut().unwrap();
+        map.insert("src".into(), "/home/user/src".into());
+        map.insert("api".into(), "@src/api".into());
+        let mut alias_hash = BTreeMap::new();
+        alias_hash.insert("CONFIG".into(), "/home/user/.config".into());
+        alias_hash.insert("HOME".into(), "/home/user".into());
+        alias_hash.insert("SRC".into(), "/home/user/src".into());
+        aliases.alias_hash = Some(alias_hash);
+        aliases
     }
 
     #[test]
-    fn existing_var() {
-        let mut aliases = MockAliases::open_with_env();
-        let alias = String::from("test1");
-        let var = String::from("TEST");
-        let res = aliases.add_env(alias, var);
-        assert!(res.is_err());
+    fn longest() {
+        let aliases = aliases();
+        assert_eq!(aliases.abbrev("/home/user/src/api/src"), "@api/src");
+        assert_eq!(aliases.abbrev("/home/user/src/web"), "@src/web");
+        assert_eq!(aliases.abbrev("/home/user/.config/nvim"), "%CONFIG/nvim");
+        assert_eq!(aliases.abbrev("/home/user"), "%HOME");
     }
 
     #[test]
-    fn existing_alias() {
-        let mut aliases = MockAliases::open_with_env();
-        let alias = String::from("test");
-        let var = String::from("TEST1");
-        let res = aliases.add_env(alias, var);
-        assert!(res.is_err());
+    fn not_covered() {
+        let aliases = aliases();
+        assert_eq!(aliases.abbrev("/usr/share"), "/usr/share");
+        assert_eq!(MockAliases::open_empty().abbrev("/usr/share"), "/usr/share");
     }
 }
 
 #[cfg(test)]
-mod tests_remove_env {
+mod tests_find {
     use super::*;
 
-    #[test]
+    fn aliases() -> Aliases {
+        let mut aliases = MockAliases::open_no_aliases();
+        let map = aliases.aliases.as_mut().unwrap();
+        for alias in &["workdir", "www", "web", "webapp", "downloads"] {
+            map.insert(alias.to_string(), "/".into());
+        }
+        aliases
+    }
+
+    #[test]
+    fn exact() {
+        let aliases = aliases();
+        assert_eq!(
+            aliases.find("web", Matching::Fuzzy).unwrap(),
+            Some("web".into())
+        );
+        assert_eq!(aliases.find("wor", Matching::Exact).unwrap(), None);
+    }
+
+    #[test]
+    fn prefix() {
+        let aliases = aliases();
+        assert_eq!(
+            aliases.find("wor", Matching::Prefix).unwrap(),
+            Some("workdir".into())
+        );
+        assert_eq!(aliases.find("wdr", Matching::Prefix).unwrap(), None);
+    }
+
+    #[test]
+    fn fuzzy() {
+        let aliases = aliases();
+        assert_eq!(
+            aliases.find("WDR", Matching::Fuzzy).unwrap(),
+            Some("workdir".into())
+        );
+        assert_eq!(
+            aliases.find("dls", Matching::Fuzzy).unwrap(),
+            Some("downloads".into())
+        );
+        assert_eq!(aliases.find("xyz", Matching::Fuzzy).unwrap(), None);
+    }
+
+    #[test]
+    fn ambiguous() {
+        let res = aliases().find("we", Matching::Prefix);
+        assert!(matches!(
+            &res,
+            Err(Error::AmbiguousAlias { name, candidates })
+                if name == "we" && candidates == &["web", "webapp"]
+        ));
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "alias 'we' is ambiguous, candidates are: web, webapp"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_add {
+    use super::*;
+
+    #[test]
+    fn to_empty_aliases() {
+        let alias = String::from("test");
+        let path = String::from("/test");
+        let mut expected_aliases: BTreeMap<String, AliasEntry> = BTreeMap::new();
+
+        expected_aliases.insert(alias.to_owned(), path.to_owned().into());
+
+        let mut aliases = MockAliases::open_no_aliases();
+        let res = aliases.add(alias, path);
+        assert!(res.is_ok());
+        assert_eq!(aliases.aliases, Some(expected_aliases));
+    }
+
+    #[test]
+    fn to_none_aliases() {
+        let alias = String::from("test");
+        let path = String::from("/test");
+        let mut expected_aliases: BTreeMap<String, AliasEntry> = BTreeMap::new();
+
+        expected_aliases.insert(alias.to_owned(), path.to_owned().into());
+
+        let mut aliases = MockAliases::open_empty();
+        let res = aliases.add(alias, path);
+        assert!(res.is_ok());
+        assert_eq!(aliases.aliases, Some(expected_aliases));
+    }
+
+    #[test]
+    fn to_filled_aliases() {
+        let alias = String::from("test2");
+        let path = String::from("/test");
+        let mut expected_aliases: BTreeMap<String, AliasEntry> = BTreeMap::new();
+
+        expected_aliases.insert(alias.to_owned(), path.to_owned().into());
+        expected_aliases.insert("test".into(), "/test/haha".into());
+        expected_aliases.insert("Home".into(), "~".into());
+
+        let mut aliases = MockAliases::open();
+        let res = aliases.add(alias, path);
+        assert!(res.is_ok());
+        assert_eq!(aliases.aliases, Some(expected_aliases));
+    }
+
+    #[test]
+    fn invalid_name() {
+        let mut aliases = MockAliases::open();
+        for alias in &["", "with/slash", "..", "-flag"] {
+            let res = aliases.add(alias.to_string(), String::from("/test"));
+            assert!(matches!(res, Err(Error::InvalidName { .. })));
+        }
+    }
+
+    #[test]
     fn existing() {
         let alias = String::from("test");
+        let path = String::from("/test");
+        let mut aliases = MockAliases::open();
+        let res = aliases.add(alias, path);
+        assert!(matches!(res, Err(Error::AliasExists(alias)) if alias == "test"));
+    }
+}
+
+#[cfg(test)]
+mod tests_add_entry {
+    use super::*;
+
+    #[test]
+    fn plain() {
+        let mut aliases = MockAliases::open_no_aliases();
+        let res = aliases.add_entry("test".into(), AliasEntry::new("/test"));
+        assert!(res.is_ok());
+        assert!(aliases.aliases.as_ref().unwrap()["test"].is_plain());
+    }
+
+    #[test]
+    fn with_description() {
+        let mut aliases = MockAliases::open_no_aliases();
+        let entry = AliasEntry::new("/test").with_description(Some("a test"));
+        let res = aliases.add_entry("test".into(), entry);
+        assert!(res.is_ok());
+        let entry = &aliases.aliases.as_ref().unwrap()["test"];
+        assert_eq!(entry.description, Some("a test".into()));
+        assert!(entry.created.is_some());
+        assert!(entry.updated.is_some());
+    }
+}
+
+#[cfg(test)]
+mod tests_list_alias_completions {
+    use super::*;
+
+    #[test]
+    fn with_descriptions() {
+        let mut aliases = MockAliases::open_no_aliases();
+        aliases
+            .add_entry(
+                "api".into(),
+                AliasEntry::new("/src/api").with_description(Some("REST API")),
+            )
+            .unwrap();
+        aliases.add("odd:name".into(), "/odd".into()).unwrap();
+        assert_eq!(
+            aliases.list_alias_completions(&[]),
+            Some("api:REST API\nodd\\:name\n".into())
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let aliases = MockAliases::open_no_aliases();
+        assert_eq!(aliases.list_alias_completions(&[]), None);
+    }
+
+    #[test]
+    fn tagged() {
+        let mut aliases = MockAliases::open();
+        aliases
+            .add_entry(
+                "api".into(),
+                AliasEntry::new("/src/api").with_tags(vec!["work"]),
+            )
+            .unwrap();
+        assert_eq!(
+            aliases.list_alias_completions(&["work".into()]),
+            Some("api\n".into())
+        );
+        assert_eq!(aliases.list_alias_completions(&["home".into()]), None);
+    }
+}
+
+#[cfg(test)]
+mod tests_remove {
+    use super::*;
+
+    #[test]
+    fn existing() {
+        let alias = String::from("test");
+        let mut aliases = MockAliases::open();
+        let res = aliases.remove(alias);
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn not_existing() {
+        let alias = String::from("not_exsting");
+        let mut aliases = MockAliases::open_no_aliases();
+        let res = aliases.remove(alias);
+        assert!(matches!(res, Err(Error::AliasNotFound(_))));
+    }
+
+    #[test]
+    fn from_empty_aliases() {
+        let alias = String::from("not_exsting");
+        let mut aliases = MockAliases::open_empty();
+        let res = aliases.remove(alias);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn from_none_aliases() {
+        let alias = String::from("not_exsting");
+        let mut aliases = MockAliases::open_no_aliases();
+        let res = aliases.remove(alias);
+        assert!(res.is_err());
+    }
+}
+
+#[cfg(test)]
+mod tests_update {
+    use super::*;
+
+    #[test]
+    fn existing() {
+        let mut aliases = MockAliases::open();
+        let res = aliases.update("test".into(), "/updated".into());
+        assert_eq!(res.unwrap(), "/test/haha");
+        assert_eq!(aliases.get("test").unwrap(), Some("/updated".into()));
+    }
+
+    #[test]
+    fn keeps_metadata_and_env() {
         let mut aliases = MockAliases::open_with_env();
-        let res = aliases.remove_env(alias);
+        aliases
+            .add_entry(
+                "api".into(),
+                AliasEntry::new("/src/api").with_description(Some("REST API")),
+            )
+            .unwrap();
+        aliases.update("api".into(), "/src/api2".into()).unwrap();
+        aliases.update("test".into(), "/updated".into()).unwrap();
+        let entry = &aliases.aliases.as_ref().unwrap()["api"];
+        assert_eq!(entry.path, "/src/api2");
+        assert_eq!(entry.description, Some("REST API".into()));
+        assert_eq!(aliases.get_env("test").unwrap(), vec!["TEST".to_string()]);
+    }
+
+    #[test]
+    fn not_existing() {
+        let mut aliases = MockAliases::open();
+        let res = aliases.update("nope".into(), "/updated".into());
+        assert!(matches!(res, Err(Error::AliasNotFound(alias)) if alias == "nope"));
+    }
+}
+
+#[cfg(test)]
+mod tests_rename {
+    use super::*;
+
+    #[test]
+    fn alias() {
+        let mut aliases = MockAliases::open();
+        let res = aliases.rename("test".into(), "renamed".into(), None);
+        assert!(res.is_ok());
+        assert_eq!(aliases.get("test").unwrap(), None);
+        assert_eq!(aliases.get("renamed").unwrap(), Some("/test/haha".into()));
+    }
+
+    #[test]
+    fn keeps_metadata() {
+        let mut aliases = MockAliases::open_no_aliases();
+        aliases
+            .add_entry(
+                "api".into(),
+                AliasEntry::new("/src/api").with_tags(vec!["work"]),
+            )
+            .unwrap();
+        aliases.rename("api".into(), "rest".into(), None).unwrap();
+        let entry = &aliases.aliases.as_ref().unwrap()["rest"];
+        assert_eq!(entry.path, "/src/api");
+        assert_eq!(entry.tags, vec![String::from("work")]);
+    }
+
+    #[test]
+    fn moves_env() {
+        let mut aliases = MockAliases::open_with_env();
+        aliases
+            .rename("test".into(), "renamed".into(), None)
+            .unwrap();
+        assert!(aliases.get_env("test").is_err());
+        assert_eq!(
+            aliases.get_env("renamed").unwrap(),
+            vec!["TEST".to_string()]
+        );
+    }
+
+    #[test]
+    fn moves_every_env() {
+        let mut aliases = MockAliases::open_with_env();
+        aliases
+            .add_env("test/src".into(), "TEST_SRC".into())
+            .unwrap();
+        aliases
+            .rename("test".into(), "renamed".into(), None)
+            .unwrap();
+        assert_eq!(
+            aliases.get_env("renamed").unwrap(),
+            vec!["TEST".to_string(), "TEST_SRC".to_string()]
+        );
+        assert_eq!(
+            aliases.get_env_entry("TEST_SRC").unwrap().target,
+            "renamed/src"
+        );
+        let res = aliases.rename("renamed".into(), "test".into(), Some("VAR".into()));
+        assert!(matches!(res, Err(Error::SeveralEnvVars(alias)) if alias == "renamed"));
+    }
+
+    #[test]
+    fn renames_env() {
+        let mut aliases = MockAliases::open_with_env();
+        aliases
+            .rename("test".into(), "renamed".into(), Some("RENAMED".into()))
+            .unwrap();
+        assert_eq!(
+            aliases.get_env("renamed").unwrap(),
+            vec!["RENAMED".to_string()]
+        );
+    }
+
+    #[test]
+    fn invalid_env() {
+        let mut aliases = MockAliases::open_with_env();
+        let res = aliases.rename("test".into(), "renamed".into(), Some("RE NAMED".into()));
+        assert!(matches!(res, Err(Error::InvalidName { name, .. }) if name == "RAD_RE NAMED"));
+        assert_eq!(aliases.get_env("test").unwrap(), vec!["TEST".to_string()]);
+    }
+
+    #[test]
+    fn not_existing() {
+        let mut aliases = MockAliases::open();
+        let res = aliases.rename("nope".into(), "renamed".into(), None);
+        assert!(matches!(res, Err(Error::AliasNotFound(alias)) if alias == "nope"));
+    }
+
+    #[test]
+    fn existing_new_alias() {
+        let mut aliases = MockAliases::open();
+        let res = aliases.rename("test".into(), "Home".into(), None);
+        assert!(matches!(res, Err(Error::AliasExists(alias)) if alias == "Home"));
+        assert_eq!(aliases.get("test").unwrap(), Some("/test/haha".into()));
+    }
+
+    #[test]
+    fn references() {
+        let mut aliases = MockAliases::open_no_aliases();
+        aliases
+            .add("company".into(), "/src/company".into())
+            .unwrap();
+        aliases.add("api".into(), "@company/api".into()).unwrap();
+        aliases.add("co".into(), "@company".into()).unwrap();
+        aliases.add("companyx".into(), "/src/x".into()).unwrap();
+        aliases.add("x".into(), "@companyx".into()).unwrap();
+        aliases
+            .rename("company".into(), "corp".into(), None)
+            .unwrap();
+        let paths = aliases.aliases.as_ref().unwrap();
+        assert_eq!(paths["api"].path, "@corp/api");
+        assert_eq!(paths["co"].path, "@corp");
+        assert_eq!(paths["x"].path, "@companyx");
+        assert_eq!(aliases.get("api").unwrap(), Some("/src/company/api".into()));
+    }
+
+    #[test]
+    fn var_without_env() {
+        let mut aliases = MockAliases::open();
+        let res = aliases.rename("test".into(), "renamed".into(), Some("VAR".into()));
+        assert!(matches!(res, Err(Error::EnvVarNotFound(alias)) if alias == "test"));
+    }
+}
+
+#[cfg(test)]
+mod tests_get_env {
+    use super::*;
+
+    #[test]
+    fn existing() {
+        let alias = "test";
+        let aliases = MockAliases::open_with_env();
+        let ret = aliases.get_env(alias);
+        assert!(ret.is_ok());
+        assert_eq!(ret.unwrap(), vec![String::from("TEST")]);
+    }
+
+    #[test]
+    fn path() {
+        let aliases = MockAliases::open_with_env();
+        assert_eq!(
+            aliases.get_env_path(&"test/src".into()).unwrap(),
+            Some(PathBuf::from("/test/haha/src"))
+        );
+        assert_eq!(aliases.get_env_path(&"nope".into()).unwrap(), None);
+    }
+
+    #[test]
+    fn not_existing() {
+        let alias = "NOPE";
+        let aliases = MockAliases::open_with_env();
+        let ret = aliases.get_env(alias);
+        assert!(matches!(ret, Err(Error::EnvVarNotFound(_))));
+    }
+
+    #[test]
+    fn from_no_env() {
+        let alias = "should_fail";
+        let aliases = MockAliases::open_no_aliases();
+        let ret = aliases.get_env(alias);
+        assert!(ret.is_err());
+    }
+
+    #[test]
+    fn from_empty_env() {
+        let alias = "should_fail";
+        let aliases = MockAliases::open_empty();
+        let ret = aliases.get_env(alias);
+        assert!(ret.is_err());
+    }
+}
+
+#[cfg(test)]
+mod tests_add_env {
+    use super::*;
+
+    #[test]
+    fn not_existing_var() {
+        let mut aliases = MockAliases::open();
+        let alias = String::from("Home");
+        let var = String::from("MY_HOME");
+        let mut expected_vars: BTreeMap<String, EnvEntry> = BTreeMap::new();
+
+        expected_vars.insert(var.to_owned(), alias.as_str().into());
+
+        let res = aliases.add_env(alias, var);
         assert!(res.is_ok());
+        assert_eq!(aliases.vars, Some(expected_vars));
+    }
+
+    #[test]
+    fn existing_var() {
+        let mut aliases = MockAliases::open_with_env();
+        let alias = String::from("test1");
+        let var = String::from("TEST");
+        let res = aliases.add_env(alias, var);
+        assert!(matches!(res, Err(Error::EnvVarTaken { var, .. }) if var == "TEST"));
+    }
+
+    #[test]
+    fn existing_alias() {
+        let mut aliases = MockAliases::open_with_env();
+        aliases.add_env("test".into(), "TEST1".into()).unwrap();
+        aliases
+            .add_env("test/src".into(), "TEST_SRC".into())
+            .unwrap();
+        assert_eq!(
+            aliases.get_env("test").unwrap(),
+            vec![
+                "TEST".to_string(),
+                "TEST1".to_string(),
+                "TEST_SRC".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn without_prefix() {
+        let mut aliases = MockAliases::open();
+        let res = aliases.add_env_entry("PATH".into(), EnvEntry::new("Home").with_prefix(false));
+        assert!(matches!(res, Err(Error::InvalidName { name, .. }) if name == "PATH"));
+        aliases
+            .add_env_entry("GOPATH".into(), EnvEntry::new("Home").with_prefix(false))
+            .unwrap();
+        assert!(!aliases.get_env_entry("GOPATH").unwrap().prefixed);
+    }
+
+    #[test]
+    fn invalid_var() {
+        let mut aliases = MockAliases::open();
+        let res = aliases.add_env("Home".into(), "MY-HOME".into());
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "invalid name 'RAD_MY-HOME': variable name can only contain ASCII letters, \
+             digits and '_', try 'MY_HOME'"
+        );
+        assert_eq!(aliases.vars, None);
+    }
+
+    #[test]
+    fn critical_var() {
+        let mut aliases = MockAliases::open();
+        aliases.set_env_prefix("");
+        let res = aliases.add_env("Home".into(), "path".into());
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "invalid name 'PATH': variable would override a critical variable, try 'path_DIR'"
+        );
+        assert!(aliases.add_env("Home".into(), "HOME_DIR".into()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests_check_env_var {
+    use super::*;
+
+    fn reason(aliases: &Aliases, var: &str) -> String {
+        match aliases.check_env_var(var, true) {
+            Err(Error::InvalidName { reason, .. }) => reason,
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn valid() {
+        let mut aliases = MockAliases::open_empty();
+        assert!(aliases.check_env_var("WD", true).is_ok());
+        assert!(aliases.check_env_var("2D", true).is_ok());
+        assert!(aliases.check_env_var("2D", false).is_err());
+        aliases.set_env_prefix("");
+        assert!(aliases.check_env_var("_wd", true).is_ok());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut aliases = MockAliases::open_empty();
+        assert_eq!(reason(&aliases, ""), "variable name cannot be empty");
+        assert_eq!(
+            reason(&aliases, "caf\u{e9}"),
+            "variable name can only contain ASCII letters, digits and '_', try 'caf_'"
+        );
+        aliases.set_env_prefix("");
+        assert_eq!(
+            reason(&aliases, "2D"),
+            "variable name cannot start with a digit, try '_2D'"
+        );
+        assert_eq!(
+            reason(&aliases, "_RAD_MATCH"),
+            "variable would override rualdi configuration, try 'RAD_MATCH'"
+        );
+        assert_eq!(
+            reason(&aliases, "_rad_match"),
+            "variable would override rualdi configuration, try 'rad_match'"
+        );
+        aliases.set_env_case(EnvCase::Keep);
+        assert_eq!(
+            reason(&aliases, "_Rad_Match"),
+            "variable would override rualdi configuration, try 'Rad_Match'"
+        );
+        aliases.set_env_case(EnvCase::Lower);
+        assert_eq!(
+            reason(&aliases, "Path"),
+            "variable would override a critical variable, try 'Path_DIR'"
+        );
+    }
+
+    #[test]
+    fn env_name() {
+        let mut aliases = MockAliases::open_empty();
+        assert_eq!(aliases.env_name("wd", true), "RAD_WD");
+        assert_eq!(aliases.env_name("GOPATH", false), "GOPATH");
+        aliases.set_env_prefix("PROJ_");
+        aliases.set_env_case(EnvCase::Keep);
+        assert_eq!(aliases.env_name("wd", true), "PROJ_wd");
+        aliases.set_env_prefix("");
+        aliases.set_env_case(EnvCase::Lower);
+        assert_eq!(aliases.env_name("WD", true), "wd");
+    }
+
+    #[test]
+    fn env_name_as_is() {
+        assert!(Aliases::check_env_name("RAD_wd").is_ok());
+        for name in &["", "1X", "$(rm)", "PATH", "_rad_match"] {
+            assert!(Aliases::check_env_name(name).is_err(), "{}", name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_remove_env {
+    use super::*;
+
+    #[test]
+    fn existing() {
+        let alias = String::from("test");
+        let mut aliases = MockAliases::open_with_env();
+        let res = aliases.remove_env(alias, &[]);
+        assert_eq!(res.unwrap(), vec![(String::from("TEST"), "test".into())]);
+    }
+
+    #[test]
+    fn some_vars() {
+        let mut aliases = MockAliases::open_with_env();
+        aliases
+            .add_env("test/src".into(), "TEST_SRC".into())
+            .unwrap();
+        let res = aliases.remove_env("test".into(), &["TEST2".into()]);
+        assert!(matches!(res, Err(Error::EnvVarNotFound(_))));
+        let res = aliases.remove_env("test".into(), &["TEST_SRC".into()]);
+        assert_eq!(
+            res.unwrap(),
+            vec![(String::from("TEST_SRC"), "test/src".into())]
+        );
+        assert_eq!(aliases.get_env("test").unwrap(), vec![String::from("TEST")]);
     }
 
     #[test]
     fn not_existing() {
         let alias = String::from("not_exsting");
         let mut aliases = MockAliases::open_with_env();
-        let res = aliases.remove_env(alias);
-        assert!(res.is_err());
+        let res = aliases.remove_env(alias, &[]);
+        assert!(matches!(res, Err(Error::EnvVarNotFound(_))));
     }
 
     #[test]
     fn from_empty_env() {
         let alias = String::from("not_exsting");
         let mut aliases = MockAliases::open_empty();
-        let res = aliases.remove_env(alias);
+        let res = aliases.remove_env(alias, &[]);
         assert!(res.is_err());
     }
 
     #[test]
-    fn from_none_env() {
-        let alias = String::from("not_exsting");
+    fn from_none_env() {
+        let alias = String::from("not_exsting");
+        let mut aliases = MockAliases::open_no_aliases();
+        let res = aliases.remove_env(alias, &[]);
+        assert!(res.is_err());
+    }
+}
+
+#[cfg(test)]
+mod tests_list {
+    use super::*;
+
+    #[test]
+    fn list_filled() {
+        let aliases = MockAliases::open();
+        let output = aliases.list(&[], Sort::Name);
+        let width = terminal_size().map(|(w, _)| w.0 as usize).unwrap_or(1);
+        let equal_line = "=".repeat(width);
+        assert!(output.is_some());
+        assert_eq!(
+            output.unwrap(),
+            format!(
+                "{}\n{: ^width$}\n{}\nHome         => ~\ntest         => /test/haha\n",
+                equal_line,
+                "ALIASES",
+                equal_line,
+                width = width - 1
+            )
+        );
+    }
+
+    #[test]
+    fn list_filled_env() {
+        let aliases = MockAliases::open_with_env();
+        let output = aliases.list(&[], Sort::Name);
+        let width = terminal_size().map(|(w, _)| w.0 as usize).unwrap_or(1);
+        let equal_line = "=".repeat(width);
+        assert!(output.is_some());
+        assert_eq!(output.unwrap(),             format!(
+                "{}\n{: ^width$}\n{}\nHome         => ~\ntest         => /test/haha\n{}\n{: ^width$}\n{}\nTEST         => test\n",
+                equal_line,
+                "ALIASES",
+                equal_line,
+                equal_line,
+                "ENVIRONMENT VARIABLES",
+                equal_line,
+                width = width - 1
+            )
+);
+    }
+
+    #[test]
+    fn list_description() {
         let mut aliases = MockAliases::open_no_aliases();
-        let res = aliases.remove_env(alias);
-        assert!(res.is_err());
+        aliases
+            .add_entry(
+                "api".into(),
+                AliasEntry::new("/src/api").with_description(Some("REST API")),
+            )
+            .unwrap();
+        let output = aliases.list(&[], Sort::Name);
+        let width = terminal_size().map(|(w, _)| w.0 as usize).unwrap_or(1);
+        let equal_line = "=".repeat(width);
+        assert_eq!(
+            output.unwrap(),
+            format!(
+                "{}\n{: ^width$}\n{}\napi          => /src/api  # REST API\n",
+                equal_line,
+                "ALIASES",
+                equal_line,
+                width = width - 1
+            )
+        );
     }
-}
 
-#[cfg(test)]
-mod tests_list {
-    use super::*;
+    #[test]
+    fn list_reference() {
+        let mut aliases = MockAliases::open_no_aliases();
+        aliases
+            .add("company".into(), "/src/company".into())
+            .unwrap();
+        aliases.add("api".into(), "@company/api".into()).unwrap();
+        let output = aliases.list(&[], Sort::Name);
+        let width = terminal_size().map(|(w, _)| w.0 as usize).unwrap_or(1);
+        let equal_line = "=".repeat(width);
+        assert_eq!(
+            output.unwrap(),
+            format!(
+                "{}\n{: ^width$}\n{}\napi          => @company/api (/src/company/api)\ncompany      => /src/company\n",
+                equal_line,
+                "ALIASES",
+                equal_line,
+                width = width - 1
+            )
+        );
+        assert_eq!(
+            aliases.list_alias(&[], Sort::Name),
+            Some("api => /src/company/api\ncompany => /src/company\n".into())
+        );
+    }
 
     #[test]
-    fn list_filled() {
-        let aliases = MockAliases::open();
-        let output = aliases.list();
+    fn list_candidates() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let existing = dir.path().to_str().unwrap();
+        let mut aliases = MockAliases::open_no_aliases();
+        let entry = AliasEntry {
+            alternatives: vec![existing.into()],
+            ..AliasEntry::new("/not/existing")
+        };
+        aliases.add_entry("x".into(), entry)?;
+        let output = aliases.list(&[], Sort::Name);
         let width = terminal_size().map(|(w, _)| w.0 as usize).unwrap_or(1);
         let equal_line = "=".repeat(width);
-        assert!(output.is_some());
         assert_eq!(
             output.unwrap(),
             format!(
-                "{}\n{: ^width$}\n{}\nHome         => ~\ntest         => /test/haha\n",
+                "{}\n{: ^width$}\n{}\nx            => /not/existing | {} (active: {})\n",
                 equal_line,
                 "ALIASES",
                 equal_line,
+                existing,
+                existing,
                 width = width - 1
             )
         );
+        assert_eq!(
+            aliases.list_alias(&[], Sort::Name),
+            Some(format!("x => {}\n", existing))
+        );
+        Ok(())
     }
 
     #[test]
-    fn list_filled_env() {
-        let aliases = MockAliases::open_with_env();
-        let output = aliases.list();
+    fn list_tagged() {
+        let mut aliases = MockAliases::open_with_env();
+        aliases
+            .add_entry(
+                "api".into(),
+                AliasEntry::new("/src/api").with_tags(vec!["work", "rust"]),
+            )
+            .unwrap();
+        let output = aliases.list(&["work".into()], Sort::Name);
         let width = terminal_size().map(|(w, _)| w.0 as usize).unwrap_or(1);
         let equal_line = "=".repeat(width);
-        assert!(output.is_some());
-        assert_eq!(output.unwrap(),             format!(
-                "{}\n{: ^width$}\n{}\nHome         => ~\ntest         => /test/haha\n{}\n{: ^width$}\n{}\nTEST         => test\n",
+        assert_eq!(
+            output.unwrap(),
+            format!(
+                "{}\n{: ^width$}\n{}\napi          => /src/api\n",
                 equal_line,
                 "ALIASES",
                 equal_line,
+                width = width - 1
+            )
+        );
+        assert!(aliases.list(&["home".into()], Sort::Name).is_none());
+    }
+
+    #[test]
+    fn list_invalid_env() {
+        let mut aliases = MockAliases::open_with_env();
+        aliases
+            .vars
+            .as_mut()
+            .unwrap()
+            .insert("my-home".into(), "Home".into());
+        let output = aliases.list(&[], Sort::Name).unwrap();
+        assert!(output.contains(
+            "my-home      => Home (invalid name 'RAD_MY-HOME': variable name can only contain \
+             ASCII letters, digits and '_', try 'my_home')\n"
+        ));
+        assert!(output.contains("TEST         => test\n"));
+    }
+
+    #[test]
+    fn list_env_without_alias() {
+        let mut aliases = MockAliases::open_with_env();
+        aliases
+            .vars
+            .as_mut()
+            .unwrap()
+            .insert("GONE".into(), "gone".into());
+        let output = aliases.list(&[], Sort::Name).unwrap();
+        assert!(output.contains("GONE         => gone\n"));
+        aliases
+            .add_entry(
+                "api".into(),
+                AliasEntry::new("/src/api").with_tags(vec!["work"]),
+            )
+            .unwrap();
+        let output = aliases.list(&["work".into()], Sort::Name).unwrap();
+        assert!(!output.contains("GONE"));
+    }
+
+    #[test]
+    fn list_grouped() {
+        let mut aliases = MockAliases::open_with_env();
+        aliases
+            .add_entry(
+                "api".into(),
+                AliasEntry::new("/src/api").with_tags(vec!["work", "rust"]),
+            )
+            .unwrap();
+        let output = aliases.list_grouped(&[], Sort::Name);
+        let width = terminal_size().map(|(w, _)| w.0 as usize).unwrap_or(1);
+        let equal_line = "=".repeat(width);
+        let banner = |title: &str| {
+            format!(
+                "{}\n{: ^width$}\n{}\n",
                 equal_line,
-                "ENVIRONMENT VARIABLES",
+                title,
                 equal_line,
                 width = width - 1
             )
-);
+        };
+        assert_eq!(
+            output.unwrap(),
+            format!(
+                "{}api          => /src/api\n{}api          => /src/api\n{}Home         => ~\ntest         => /test/haha\n{}TEST         => test\n",
+                banner("TAG: rust"),
+                banner("TAG: work"),
+                banner("UNTAGGED"),
+                banner("ENVIRONMENT VARIABLES"),
+            )
+        );
     }
 
     #[test]
     fn list_empty() {
         let aliases = MockAliases::open_empty();
-        let output = aliases.list();
+        let output = aliases.list(&[], Sort::Name);
         assert!(output.is_none());
     }
 
     #[test]
     fn list_no_aliases() {
         let aliases = MockAliases::open_no_aliases();
-        let output = aliases.list();
+        let output = aliases.list(&[], Sort::Name);
         assert!(output.is_none());
     }
 }
@@ -1199,9 +3455,92 @@ mod tests_list_env {
     }
 }
 
+#[cfg(test)]
+mod tests_hosts {
+    use super::*;
+
+    fn with_hosts(aliases: &mut Aliases) {
+        let mut buildbox = HostSection::default();
+        buildbox.aliases.insert("build".into(), "/build".into());
+        buildbox
+            .aliases
+            .insert("test".into(), "/buildbox/test".into());
+        let mut laptops = HostSection {
+            hostname: Some("laptop-*".into()),
+            ..Default::default()
+        };
+        laptops.aliases.insert("vpn".into(), "~/vpn".into());
+
+        let mut hosts = BTreeMap::new();
+        hosts.insert("buildbox".to_owned(), buildbox);
+        hosts.insert("laptops".to_owned(), laptops);
+        aliases.hosts = Some(hosts);
+    }
+
+    #[test]
+    fn matching_section() {
+        let mut aliases = MockAliases::open();
+        with_hosts(&mut aliases);
+        aliases.apply_hosts(Some("buildbox"));
+        assert_eq!(aliases.get("build").unwrap(), Some("/build".into()));
+        assert_eq!(aliases.get("test").unwrap(), Some("/buildbox/test".into()));
+        assert_eq!(aliases.get("vpn").unwrap(), None);
+        assert_eq!(aliases.origin("test"), Some("buildbox"));
+        assert_eq!(aliases.origin("Home"), None);
+    }
+
+    #[test]
+    fn no_matching_section() {
+        let mut aliases = MockAliases::open();
+        with_hosts(&mut aliases);
+        aliases.apply_hosts(Some("desktop"));
+        assert_eq!(aliases.get("build").unwrap(), None);
+        assert_eq!(aliases.get("test").unwrap(), Some("/test/haha".into()));
+        assert_eq!(aliases.origin("test"), None);
+    }
+
+    #[test]
+    fn rename_and_remove() {
+        let mut aliases = MockAliases::open();
+        with_hosts(&mut aliases);
+        aliases.apply_hosts(Some("laptop-42"));
+        aliases.rename("vpn".into(), "tunnel".into(), None).unwrap();
+        assert_eq!(aliases.origin("tunnel"), Some("laptops"));
+        aliases.remove("tunnel".into()).unwrap();
+        assert_eq!(aliases.origin("tunnel"), None);
+    }
+
+    #[test]
+    fn remove_restores_base() {
+        let mut aliases = MockAliases::open();
+        with_hosts(&mut aliases);
+        aliases.apply_hosts(Some("buildbox"));
+        assert_eq!(
+            aliases.remove("test".into()).unwrap(),
+            Some("/test/haha".into())
+        );
+        assert_eq!(aliases.get("test").unwrap(), Some("/test/haha".into()));
+        assert_eq!(aliases.origin("test"), None);
+        assert_eq!(aliases.remove("test".into()).unwrap(), None);
+        assert!(!aliases.contains("test"));
+    }
+
+    #[test]
+    fn list_origin() {
+        let mut aliases = MockAliases::open();
+        with_hosts(&mut aliases);
+        aliases.apply_hosts(Some("buildbox"));
+        let output = aliases.list_alias(&[], Sort::Name).unwrap();
+        assert!(output.contains("build => /build"));
+        let output = aliases.list(&[], Sort::Name).unwrap();
+        assert!(output.contains(" [host: buildbox]"));
+    }
+}
+
 #[cfg(test)]
 mod test_open {
     use super::*;
+    use anyhow::Result;
 
     #[test]
     fn open_config_not_existing() -> Result<()> {
@@ -1258,9 +3597,238 @@ mod test_open {
     }
 }
 
+#[cfg(test)]
+mod test_migrate {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn unversioned_alias_map() -> Result<()> {
+        let aliases_file = TmpConfig::create_dir()?.with_empty()?;
+        let dir = aliases_file.tmp_dir.path().to_path_buf();
+        let original = r#"# Rualdi aliases configuration file
+[aliases]
+test = "/test/haha"
+
+# Shortened paths
+[alias_map]
+CONFIG = "~/.config"
+"#;
+        fs::write(dir.join("rualdi.toml"), original)?;
+
+        let aliases = Aliases::open(dir.clone())?;
+        assert_eq!(aliases.version, CONFIG_VERSION);
+        assert_eq!(
+            aliases.alias_hash.as_ref().and_then(|h| h.get("CONFIG")),
+            Some(&"~/.config".to_string())
+        );
+        drop(aliases);
+
+        assert_eq!(
+            fs::read_to_string(dir.join("rualdi.toml.v0.bak"))?,
+            original
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("rualdi.toml"))?,
+            format!(
+                r#"# Rualdi aliases configuration file
+version = {}
+
+[aliases]
+test = "/test/haha"
+
+# Shortened paths
+[alias_hash]
+CONFIG = "~/.config"
+"#,
+                CONFIG_VERSION
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn environment_by_variable() -> Result<()> {
+        let aliases_file = TmpConfig::create_dir()?.with_empty()?;
+        let dir = aliases_file.tmp_dir.path().to_path_buf();
+        let version = CONFIG_VERSION - 1;
+        fs::write(
+            dir.join("rualdi.toml"),
+            format!(
+                "version = {}\n\n[aliases]\napi = \"/api\"\ndocs = \"/docs\"\n\n[environment]\napi = \"API\"\ndocs = \"DOCS\"\n",
+                version
+            ),
+        )?;
+
+        let aliases = Aliases::open(dir.clone())?;
+        assert_eq!(aliases.get_env("api")?, vec!["API".to_string()]);
+        assert_eq!(aliases.get_env("docs")?, vec!["DOCS".to_string()]);
+        drop(aliases);
+
+        assert!(dir.join(format!("rualdi.toml.v{}.bak", version)).exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("rualdi.toml"))?,
+            format!(
+                "version = {}\n\n[aliases]\napi = \"/api\"\ndocs = \"/docs\"\n\n[environment]\nAPI = \"api\"\nDOCS = \"docs\"\n",
+                CONFIG_VERSION
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn current_version_untouched() -> Result<()> {
+        let aliases_file = TmpConfig::create_dir()?.with_empty()?;
+        let dir = aliases_file.tmp_dir.path().to_path_buf();
+        let original = format!(
+            "version = {}\n\n[aliases]\ntest = \"/test\"\n",
+            CONFIG_VERSION
+        );
+        fs::write(dir.join("rualdi.toml"), &original)?;
+
+        let aliases = Aliases::open(dir.clone())?;
+        drop(aliases);
+
+        assert!(!dir.join("rualdi.toml.v0.bak").exists());
+        assert_eq!(fs::read_to_string(dir.join("rualdi.toml"))?, original);
+        Ok(())
+    }
+
+    #[test]
+    fn read_only_not_written() -> Result<()> {
+        let aliases_file = TmpConfig::create_dir()?.with_empty()?;
+        let dir = aliases_file.tmp_dir.path().to_path_buf();
+        let original = "[aliases]\ntest = \"/test\"\n\n[alias_map]\nCONFIG = \"~/.config\"\n";
+        fs::write(dir.join("rualdi.toml"), original)?;
+
+        let mut aliases = Aliases::open_read_only(dir.clone())?;
+        assert_eq!(aliases.version, CONFIG_VERSION);
+        assert!(aliases
+            .alias_hash
+            .as_ref()
+            .is_some_and(|h| h.contains_key("CONFIG")));
+        aliases.add("other".into(), "/other".into())?;
+        drop(aliases);
+
+        assert!(!dir.join("rualdi.toml.v0.bak").exists());
+        assert!(!dir.join(".rualdi.lock").exists());
+        assert_eq!(fs::read_to_string(dir.join("rualdi.toml"))?, original);
+
+        let missing = dir.join("missing");
+        let aliases = Aliases::open_read_only(missing.clone())?;
+        assert!(aliases.aliases.is_none());
+        assert!(!missing.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn newer_version() -> Result<()> {
+        let aliases_file = TmpConfig::create_dir()?.with_empty()?;
+        let dir = aliases_file.tmp_dir.path().to_path_buf();
+        fs::write(
+            dir.join("rualdi.toml"),
+            format!("version = {}\n", CONFIG_VERSION + 1),
+        )?;
+
+        let aliases = Aliases::open(dir);
+        assert!(matches!(
+            aliases,
+            Err(Error::UnsupportedVersion { found, .. }) if found == CONFIG_VERSION as i64 + 1
+        ));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_visits {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn record_and_sort() -> Result<()> {
+        let aliases_file = TmpConfig::create_dir()?
+            .with_base()?
+            .with_content(toml::toml![
+                [aliases]
+                alpha = "/alpha"
+                beta = "/beta"
+            ])?;
+        let dir = aliases_file.tmp_dir.path().to_path_buf();
+
+        let aliases = Aliases::open(dir.clone())?;
+        aliases.record_visit("beta")?;
+        aliases.record_visit("beta")?;
+        assert!(matches!(
+            aliases.record_visit("gamma"),
+            Err(Error::AliasNotFound(_))
+        ));
+        drop(aliases);
+
+        let aliases = Aliases::open(dir)?;
+        assert_eq!(aliases.visits()?.get("beta").map(|v| v.count), Some(2));
+        assert_eq!(
+            aliases.list_alias(&[], Sort::Frecency),
+            Some("beta => /beta\nalpha => /alpha\n".into())
+        );
+        assert_eq!(
+            aliases.list_alias(&[], Sort::Name),
+            Some("alpha => /alpha\nbeta => /beta\n".into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn stats() -> Result<()> {
+        let aliases_file = TmpConfig::create_dir()?
+            .with_base()?
+            .with_content(toml::toml![
+                [aliases]
+                alpha = "/alpha"
+                beta = "/beta"
+            ])?;
+        let aliases = Aliases::open(aliases_file.tmp_dir.path().to_path_buf())?;
+        aliases.record_visit("beta")?;
+        let now = aliases.visits()?.get("beta").unwrap().last_access + 10;
+
+        let stats = aliases.stats_at(now).unwrap();
+        let lines: Vec<&str> = stats.lines().skip(3).collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("beta         =>     1 visits, last "));
+        assert!(lines[0].ends_with(", frecency 4"));
+        assert_eq!(lines[1], "alpha        =>     0 visits");
+        Ok(())
+    }
+
+    #[test]
+    fn follow_rename_and_remove() -> Result<()> {
+        let aliases_file = TmpConfig::create_dir()?
+            .with_base()?
+            .with_content(toml::toml![
+                [aliases]
+                alpha = "/alpha"
+                beta = "/beta"
+            ])?;
+        let mut aliases = Aliases::open(aliases_file.tmp_dir.path().to_path_buf())?;
+        aliases.record_visit("alpha")?;
+        aliases.record_visit("beta")?;
+
+        aliases.rename("alpha".into(), "gamma".into(), None)?;
+        aliases.remove("beta".into())?;
+        // Visits follow the aliases only once they are saved
+        assert_eq!(aliases.visits()?.get("alpha").map(|v| v.count), Some(1));
+        aliases.save()?;
+        let visits = aliases.visits()?;
+        assert!(visits.get("alpha").is_none());
+        assert_eq!(visits.get("gamma").map(|v| v.count), Some(1));
+        assert!(visits.get("beta").is_none());
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test_save {
     use super::*;
+    use anyhow::Result;
 
     #[test]
     fn should_saved() -> Result<()> {
@@ -1301,6 +3869,166 @@ mod test_save {
         Ok(())
     }
 
+    #[test]
+    fn saved_content() -> Result<()> {
+        let aliases_file = TmpConfig::create_dir()?.with_base()?;
+        let dir = aliases_file.tmp_dir.path().to_path_buf();
+
+        let mut aliases = Aliases::open(dir.clone())?;
+        aliases.add("saved".into(), "/saved".into())?;
+        aliases.save()?;
+        drop(aliases);
+
+        let aliases = Aliases::open(dir)?;
+        assert_eq!(aliases.get("saved").unwrap(), Some("/saved".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn saved_entry_dates() -> Result<()> {
+        let aliases_file = TmpConfig::create_dir()?.with_base()?;
+        let dir = aliases_file.tmp_dir.path().to_path_buf();
+
+        let mut aliases = Aliases::open(dir.clone())?;
+        let entry = AliasEntry::new("/saved").with_description(Some("saved"));
+        aliases.add_entry("saved".into(), entry)?;
+        aliases.save()?;
+        drop(aliases);
+
+        let content = fs::read_to_string(dir.join("rualdi.toml"))?;
+        assert!(!content.contains("$__toml_private_datetime"));
+        let aliases = Aliases::open(dir)?;
+        let entry = &aliases.aliases.as_ref().unwrap()["saved"];
+        assert!(entry.created.is_some());
+        assert_eq!(entry.created, entry.updated);
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_comments_and_layout() -> Result<()> {
+        let aliases_file = TmpConfig::create_dir()?.with_empty()?;
+        let dir = aliases_file.tmp_dir.path().to_path_buf();
+        fs::write(
+            dir.join("rualdi.toml"),
+            format!(
+                r#"# My aliases
+version = {}
+
+[aliases]
+# Projects
+zeta = "/zeta"   # last one
+alpha = "/alpha"
+gone = "/gone"
+
+[colors]
+alias = "yellow" # my favorite
+"#,
+                CONFIG_VERSION
+            ),
+        )?;
+
+        let mut aliases = Aliases::open(dir.clone())?;
+        aliases.remove("gone".into())?;
+        aliases.add("beta".into(), "/beta".into())?;
+        aliases.save()?;
+
+        assert_eq!(
+            fs::read_to_string(dir.join("rualdi.toml"))?,
+            format!(
+                r#"# My aliases
+version = {}
+
+[aliases]
+# Projects
+zeta = "/zeta"   # last one
+alpha = "/alpha"
+beta = "/beta"
+
+[colors]
+alias = "yellow" # my favorite
+"#,
+                CONFIG_VERSION
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn concurrent_saves_are_not_lost() -> Result<()> {
+        let aliases_file = TmpConfig::create_dir()?.with_base()?;
+        let dir = aliases_file.tmp_dir.path().to_path_buf();
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let dir = dir.clone();
+                std::thread::spawn(move || {
+                    let mut aliases = Aliases::open(dir).unwrap();
+                    aliases
+                        .add(format!("alias{}", i), format!("/path{}", i))
+                        .unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let aliases = Aliases::open(dir)?;
+        assert_eq!(aliases.aliases.as_ref().map(|a| a.len()), Some(8));
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_host_sections() -> Result<()> {
+        std::env::set_var("RUALDI_TEST_HOSTS_SAVE", "1");
+        let aliases_file = TmpConfig::create_dir()?.with_empty()?;
+        let dir = aliases_file.tmp_dir.path().to_path_buf();
+        fs::write(
+            dir.join("rualdi.toml"),
+            format!(
+                r#"version = {}
+
+[aliases]
+build = "/build"
+
+[hosts.work]
+env = {{ RUALDI_TEST_HOSTS_SAVE = "1" }}
+
+[hosts.work.aliases]
+build = "/work/build"
+vpn = "/work/vpn"
+"#,
+                CONFIG_VERSION
+            ),
+        )?;
+
+        let mut aliases = Aliases::open(dir.clone())?;
+        assert_eq!(aliases.get("build")?, Some("/work/build".into()));
+        aliases.remove("vpn".into())?;
+        aliases.add("docs".into(), "/docs".into())?;
+        aliases.save()?;
+
+        assert_eq!(
+            fs::read_to_string(dir.join("rualdi.toml"))?,
+            format!(
+                r#"version = {}
+
+[aliases]
+build = "/build"
+docs = "/docs"
+
+[hosts.work]
+env = {{ RUALDI_TEST_HOSTS_SAVE = "1" }}
+
+[hosts.work.aliases]
+build = "/work/build"
+"#,
+                CONFIG_VERSION
+            )
+        );
+        Ok(())
+    }
+
     #[test]
     fn should_not_opened() -> Result<()> {
         let aliases_file = TmpConfig::create_dir()?
@@ -0,0 +1,123 @@
+//! Usage statistics of aliases, kept in a sidecar file next to the
+//! aliases file so recording a jump never rewrites the user configuration
+use crate::{Error, Result};
+use serde_derive::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+const WEEK: i64 = 7 * DAY;
+
+/// Number of jumps to an alias and time of the last one
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Visit {
+    pub count: u64,
+    /// Unix timestamp in seconds
+    pub last_access: i64,
+}
+
+impl Visit {
+    /// Score mixing frequency and recency of the visits, as zoxide does,
+    /// visits in the last hour weight four times more than older ones
+    pub fn frecency(&self, now: i64) -> f64 {
+        let age = now - self.last_access;
+        let weight = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        self.count as f64 * weight
+    }
+}
+
+/// Visits of every alias
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Visits {
+    #[serde(default)]
+    visits: BTreeMap<String, Visit>,
+}
+
+impl Visits {
+    /// Read visits from `path`, a missing file holds no visits
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).map_err(Error::parse(path)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Visits::default()),
+            Err(e) => Err(Error::io(format!(
+                "could not read visits file: '{}'",
+                path.display()
+            ))(e)),
+        }
+    }
+
+    /// Count a visit of `alias` at `now`
+    pub(crate) fn record(&mut self, alias: &str, now: i64) {
+        let visit = self.visits.entry(alias.into()).or_default();
+        visit.count += 1;
+        visit.last_access = now;
+    }
+
+    /// Forget the visits of `alias`, returning them
+    pub(crate) fn remove(&mut self, alias: &str) -> Option<Visit> {
+        self.visits.remove(alias)
+    }
+
+    /// Set the visits of `alias`
+    pub(crate) fn insert(&mut self, alias: &str, visit: Visit) {
+        self.visits.insert(alias.into(), visit);
+    }
+
+    /// Get visits of `alias`
+    pub fn get(&self, alias: &str) -> Option<&Visit> {
+        self.visits.get(alias)
+    }
+
+    /// Get frecency of `alias`, 0 if never visited
+    pub fn frecency(&self, alias: &str, now: i64) -> f64 {
+        self.get(alias).map(|v| v.frecency(now)).unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frecency() {
+        let visit = Visit {
+            count: 2,
+            last_access: 0,
+        };
+        assert_eq!(visit.frecency(10), 8.0);
+        assert_eq!(visit.frecency(2 * HOUR), 4.0);
+        assert_eq!(visit.frecency(2 * DAY), 1.0);
+        assert_eq!(visit.frecency(2 * WEEK), 0.5);
+    }
+
+    #[test]
+    fn record() {
+        let mut visits = Visits::default();
+        visits.record("test", 10);
+        visits.record("test", 20);
+        assert_eq!(
+            visits.get("test"),
+            Some(&Visit {
+                count: 2,
+                last_access: 20
+            })
+        );
+        assert_eq!(visits.frecency("other", 20), 0.0);
+    }
+
+    #[test]
+    fn load_missing() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let visits = Visits::load(&dir.path().join("missing.toml"))?;
+        assert!(visits.get("test").is_none());
+        Ok(())
+    }
+}
@@ -3,12 +3,20 @@ pub const ZSH_COMPLETION_REP: &[(&str, &str)] = &[
     (
         r#"            (a)
 _arguments "${_arguments_options[@]}" \
+'-d+[Description shown when listing and completing aliases]' \
+'--description=[Description shown when listing and completing aliases]' \
+'*-t+[Tag used to group and filter aliases, can be repeated]' \
+'*--tag=[Tag used to group and filter aliases, can be repeated]' \
+'(-L --logical)-P[Store the target of symlinks in path, default when _RAD_RESOLVE_SYMLINKS is set]' \
+'(-L --logical)--physical[Store the target of symlinks in path, default when _RAD_RESOLVE_SYMLINKS is set]' \
+'-L[Store symlinks in path as is]' \
+'--logical[Store symlinks in path as is]' \
 '-h[Prints help information]' \
 '--help[Prints help information]' \
 '-V[Prints version information]' \
 '--version[Prints version information]' \
 ':alias -- Alias to path:_files' \
-'::path -- Path to aliasing, if not provided current directory is used:_files' \
+'::path -- Path to aliasing, if not provided current directory is used. A path starting with `@alias` is relative to another alias:_files' \
 && ret=0
 ;;
 (add)"#,
@@ -17,11 +25,12 @@ _arguments "${_arguments_options[@]}" \
     (
         r#"(ax)
 _arguments "${_arguments_options[@]}" \
+'--no-prefix[Export the variable as is, without the configured prefix and case]' \
 '-h[Prints help information]' \
 '--help[Prints help information]' \
 '-V[Prints version information]' \
 '--version[Prints version information]' \
-':alias -- Alias to link:_files' \
+':alias -- Alias to link, may be followed by a subpath as `alias/sub`:_files' \
 '::var -- Environment variable to link on alias, if not provided alias is used:_files' \
 && ret=0
 ;;
@@ -32,6 +41,10 @@ _arguments "${_arguments_options[@]}" \
         r#"(i)
 _arguments "${_arguments_options[@]}" \
 '--cmd=[Renames the '\''rad'\'' command and corresponding aliases]' \
+'--env-prefix=[Prefix of the alias environment variables, `_RAD_ENV_PREFIX` or `RAD_` by default]' \
+'--env-case=[Case of the alias environment variable names, `_RAD_ENV_CASE` or upper by default]: :(upper lower keep)' \
+'--prompt[Keeps the current directory abbreviated with aliases in `$rualdi_prompt`]' \
+'--env-hook[Reconciles alias environment variables with the configuration file before each prompt following a change of the file]' \
 '-h[Prints help information]' \
 '--help[Prints help information]' \
 '-V[Prints version information]' \
@@ -45,37 +58,39 @@ _arguments "${_arguments_options[@]}" \
     (
         r#"(l)
 _arguments "${_arguments_options[@]}" \
+'*-t+[Only list aliases carrying this tag, can be repeated]' \
+'*--tag=[Only list aliases carrying this tag, can be repeated]' \
+'-s+[Order of listed aliases]: :(name frecency)' \
+'--sort=[Order of listed aliases]: :(name frecency)' \
+'-g[Print a section per tag]' \
+'--group[Print a section per tag]' \
 '-h[Prints help information]' \
 '--help[Prints help information]' \
 '-V[Prints version information]' \
 '--version[Prints version information]' \
 && ret=0
 ;;
-(list)
-_arguments "${_arguments_options[@]}" \
-'-h[Prints help information]' \
-'--help[Prints help information]' \
-'-V[Prints version information]' \
-'--version[Prints version information]' \
-&& ret=0
-;;
-(la)
-_arguments "${_arguments_options[@]}" \
-'-h[Prints help information]' \
-'--help[Prints help information]' \
-'-V[Prints version information]' \
-'--version[Prints version information]' \
-&& ret=0
-;;
-(list-alias)
+(list)"#,
+        r#"(list|l)"#,
+    ),
+    (
+        r#"(la)
 _arguments "${_arguments_options[@]}" \
+'*-t+[Only list aliases carrying this tag, can be repeated]' \
+'*--tag=[Only list aliases carrying this tag, can be repeated]' \
+'-s+[Order of listed aliases]: :(name frecency)' \
+'--sort=[Order of listed aliases]: :(name frecency)' \
 '-h[Prints help information]' \
 '--help[Prints help information]' \
 '-V[Prints version information]' \
 '--version[Prints version information]' \
 && ret=0
 ;;
-(lx)
+(list-alias)"#,
+        r#"(list-alias|la)"#,
+    ),
+    (
+        r#"(lx)
 _arguments "${_arguments_options[@]}" \
 '-h[Prints help information]' \
 '--help[Prints help information]' \
@@ -84,11 +99,12 @@ _arguments "${_arguments_options[@]}" \
 && ret=0
 ;;
 (list-env)"#,
-        r#"(list|l|list-alias|la|list-env|lx)"#,
+        r#"(list-env|lx)"#,
     ),
     (
         r#"(r)
 _arguments "${_arguments_options[@]}" \
+'--porcelain[Print the names removed environment variables were exported as, one per line, other messages are printed on standard error]' \
 '-h[Prints help information]' \
 '--help[Prints help information]' \
 '-V[Prints version information]' \
@@ -102,19 +118,40 @@ _arguments "${_arguments_options[@]}" \
     (
         r#"(rx)
 _arguments "${_arguments_options[@]}" \
+'--porcelain[Print the names removed environment variables were exported as, one per line, other messages are printed on standard error]' \
 '-h[Prints help information]' \
 '--help[Prints help information]' \
 '-V[Prints version information]' \
 '--version[Prints version information]' \
-':alias -- Alias for which to remove the environment variable:_files' \
+':alias -- Alias for which to remove the environment variables:_files' \
+'::vars -- Environment variables to remove, all variables of alias if not provided:_files' \
 && ret=0
 ;;
 (remove-env)"#,
         r#"(remove-env|rx)"#,
     ),
+    (
+        r#"(mv)
+_arguments "${_arguments_options[@]}" \
+'-h[Prints help information]' \
+'--help[Prints help information]' \
+'-V[Prints version information]' \
+'--version[Prints version information]' \
+':alias -- Alias to rename:_files' \
+':new-alias -- New name of the alias:_files' \
+'::var -- New name of the environment variable linked to the alias, only when the alias has a single one:_files' \
+&& ret=0
+;;
+(rename)"#,
+        r#"(rename|mv)"#,
+    ),
     (
         r#"(res)
 _arguments "${_arguments_options[@]}" \
+'--record[Record the jump to the alias to rank aliases by frecency]' \
+'(--no-check --ancestor)--mkdir[Create the missing directories of the resolved path, which must be below the path of an alias]' \
+'(--ancestor)--no-check[Print the resolved path without checking it exists]' \
+'--ancestor[Fall back to the deepest existing ancestor of a missing path]' \
 '-h[Prints help information]' \
 '--help[Prints help information]' \
 '-V[Prints version information]' \
@@ -128,19 +165,36 @@ _arguments "${_arguments_options[@]}" \
     (
         r#"(resx)
 _arguments "${_arguments_options[@]}" \
+'--exported[Print the names variables are exported as, with prefix and case]' \
 '-h[Prints help information]' \
 '--help[Prints help information]' \
 '-V[Prints version information]' \
 '--version[Prints version information]' \
-':alias -- Alias for which to find environment variable:_files' \
+':alias -- Alias for which to find environment variables:_files' \
 && ret=0
 ;;
 (resolve-env)"#,
         r#"(resolve-env|resx)"#,
     ),
+    (
+        r#"(s)
+_arguments "${_arguments_options[@]}" \
+'-h[Prints help information]' \
+'--help[Prints help information]' \
+'-V[Prints version information]' \
+'--version[Prints version information]' \
+':alias -- Alias to update:_files' \
+'::path -- New path of the alias, if not provided current directory is used. A path starting with `@alias` is relative to another alias:_files' \
+&& ret=0
+;;
+(set)"#,
+        r#"(set|s)"#,
+    ),
     (
         r#"(comp)
 _arguments "${_arguments_options[@]}" \
+'*-t+[Only list aliases carrying this tag, can be repeated]' \
+'*--tag=[Only list aliases carrying this tag, can be repeated]' \
 '-h[Prints help information]' \
 '--help[Prints help information]' \
 '-V[Prints version information]' \
@@ -167,6 +221,13 @@ _rualdi__a_commands() {
     )
     _describe -t commands 'rualdi a commands' commands \"$@\"
 }
+(( $+functions[_rualdi__abbrev_commands] )) ||
+_rualdi__abbrev_commands() {
+    local commands; commands=(
+\x20\x20\x20\x20\x20\x20\x20\x20
+    )
+    _describe -t commands 'rualdi abbrev commands' commands \"$@\"
+}
 (( $+functions[_rualdi__add_commands] )) ||
 _rualdi__add_commands() {
     local commands; commands=(
@@ -216,6 +277,20 @@ _rualdi__completions_commands() {
     )
     _describe -t commands 'rualdi completions commands' commands \"$@\"
 }
+(( $+functions[_rualdi__env_commands] )) ||
+_rualdi__env_commands() {
+    local commands; commands=(
+\x20\x20\x20\x20\x20\x20\x20\x20
+    )
+    _describe -t commands 'rualdi env commands' commands \"$@\"
+}
+(( $+functions[_rualdi__export-env_commands] )) ||
+_rualdi__export-env_commands() {
+    local commands; commands=(
+\x20\x20\x20\x20\x20\x20\x20\x20
+    )
+    _describe -t commands 'rualdi export-env commands' commands \"$@\"
+}
 (( $+functions[_i_commands] )) ||
 _i_commands() {
     local commands; commands=(
@@ -300,6 +375,20 @@ _rualdi__lx_commands() {
     )
     _describe -t commands 'rualdi lx commands' commands \"$@\"
 }
+(( $+functions[_mv_commands] )) ||
+_mv_commands() {
+    local commands; commands=(
+\x20\x20\x20\x20\x20\x20\x20\x20
+    )
+    _describe -t commands 'mv commands' commands \"$@\"
+}
+(( $+functions[_rualdi__mv_commands] )) ||
+_rualdi__mv_commands() {
+    local commands; commands=(
+\x20\x20\x20\x20\x20\x20\x20\x20
+    )
+    _describe -t commands 'rualdi mv commands' commands \"$@\"
+}
 (( $+functions[_r_commands] )) ||
 _r_commands() {
     local commands; commands=(
@@ -328,6 +417,13 @@ _rualdi__remove-env_commands() {
     )
     _describe -t commands 'rualdi remove-env commands' commands \"$@\"
 }
+(( $+functions[_rualdi__rename_commands] )) ||
+_rualdi__rename_commands() {
+    local commands; commands=(
+\x20\x20\x20\x20\x20\x20\x20\x20
+    )
+    _describe -t commands 'rualdi rename commands' commands \"$@\"
+}
 (( $+functions[_res_commands] )) ||
 _res_commands() {
     local commands; commands=(
@@ -384,6 +480,41 @@ _rx_commands() {
     )
     _describe -t commands 'rx commands' commands \"$@\"
 }
+(( $+functions[_rualdi__s_commands] )) ||
+_rualdi__s_commands() {
+    local commands; commands=(
+\x20\x20\x20\x20\x20\x20\x20\x20
+    )
+    _describe -t commands 'rualdi s commands' commands \"$@\"
+}
+(( $+functions[_s_commands] )) ||
+_s_commands() {
+    local commands; commands=(
+\x20\x20\x20\x20\x20\x20\x20\x20
+    )
+    _describe -t commands 's commands' commands \"$@\"
+}
+(( $+functions[_rualdi__set_commands] )) ||
+_rualdi__set_commands() {
+    local commands; commands=(
+\x20\x20\x20\x20\x20\x20\x20\x20
+    )
+    _describe -t commands 'rualdi set commands' commands \"$@\"
+}
+(( $+functions[_rualdi__stats_commands] )) ||
+_rualdi__stats_commands() {
+    local commands; commands=(
+\x20\x20\x20\x20\x20\x20\x20\x20
+    )
+    _describe -t commands 'rualdi stats commands' commands \"$@\"
+}
+(( $+functions[_rualdi__which_commands] )) ||
+_rualdi__which_commands() {
+    local commands; commands=(
+\x20\x20\x20\x20\x20\x20\x20\x20
+    )
+    _describe -t commands 'rualdi which commands' commands \"$@\"
+}
 ",
         r#""#,
     ),
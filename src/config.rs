@@ -1,4 +1,6 @@
-use anyhow::{bail, Context, Result};
+use crate::utils::PathMode;
+use anyhow::{anyhow, bail, Context, Result};
+use rualdlib::{Aliases, EnvCase, Matching};
 #[cfg(test)]
 use serial_test::serial;
 use std::{env, fs, path::PathBuf};
@@ -50,6 +52,79 @@ pub fn rad_resolve_symlinks() -> bool {
     }
 }
 
+/// How paths are resolved, symlinks are followed when `_RAD_RESOLVE_SYMLINKS` is set
+pub fn rad_path_mode() -> PathMode {
+    if rad_resolve_symlinks() {
+        PathMode::Physical
+    } else {
+        PathMode::Logical
+    }
+}
+
+/// How strictly `rad` matches aliases, exact matching by default
+pub fn rad_match() -> Result<Matching> {
+    match env::var("_RAD_MATCH") {
+        Ok(var) if !var.is_empty() => var
+            .parse()
+            .map_err(|e| anyhow!("invalid value of _RAD_MATCH: {}", e)),
+        _ => Ok(Matching::Exact),
+    }
+}
+
+/// Prefix of the exported alias variables, `RAD_` unless `_RAD_ENV_PREFIX`
+/// is set, an empty prefix included
+pub fn rad_env_prefix() -> Result<String> {
+    match env::var("_RAD_ENV_PREFIX") {
+        Ok(var) => {
+            parse_env_prefix(&var).map_err(|e| anyhow!("invalid value of _RAD_ENV_PREFIX: {}", e))
+        }
+        Err(_) => Ok(String::from("RAD_")),
+    }
+}
+
+/// Check a prefix only holds characters allowed in variable names
+pub fn parse_env_prefix(prefix: &str) -> std::result::Result<String, String> {
+    let valid = prefix
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !prefix.starts_with(|c: char| c.is_ascii_digit());
+    if valid {
+        Ok(prefix.to_owned())
+    } else {
+        Err(format!("'{}' is not a valid variable prefix", prefix))
+    }
+}
+
+/// How alias variable names are cased, upper case by default
+pub fn rad_env_case() -> Result<EnvCase> {
+    match env::var("_RAD_ENV_CASE") {
+        Ok(var) if !var.is_empty() => var
+            .parse()
+            .map_err(|e| anyhow!("invalid value of _RAD_ENV_CASE: {}", e)),
+        _ => Ok(EnvCase::Upper),
+    }
+}
+
+/// Names of the variables exported by the last `rualdi env --track`,
+/// recorded by the shell in `_RAD_EXPORTED_VARS`, names which could not have
+/// been exported, such as `PATH`, are ignored so that they are never unset
+pub fn rad_exported_vars() -> Vec<String> {
+    env::var("_RAD_EXPORTED_VARS")
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter(|name| Aliases::check_env_name(name).is_ok())
+        .map(String::from)
+        .collect()
+}
+
+/// Name the variables of aliases with the prefix and case
+/// of `_RAD_ENV_PREFIX` and `_RAD_ENV_CASE`
+pub fn set_env_naming(aliases: &mut Aliases) -> Result<()> {
+    aliases.set_env_prefix(rad_env_prefix()?);
+    aliases.set_env_case(rad_env_case()?);
+    Ok(())
+}
+
 // pub fn fzf_opts() -> Option<OsString> {
 //     env::var_os("_RAD_FZF_OPTS")
 // }
@@ -77,6 +152,7 @@ mod tests {
     fn resolve_symlinks() {
         std::env::set_var("_RAD_RESOLVE_SYMLINKS", "1");
         assert!(rad_resolve_symlinks());
+        assert_eq!(rad_path_mode(), PathMode::Physical);
         std::env::set_var("_RAD_RESOLVE_SYMLINKS", "0");
     }
 
@@ -84,5 +160,58 @@ mod tests {
     #[serial]
     fn no_resolve_symlinks() {
         assert!(!rad_resolve_symlinks());
+        assert_eq!(rad_path_mode(), PathMode::Logical);
+    }
+
+    #[test]
+    #[serial]
+    fn default_match() {
+        assert_eq!(rad_match().unwrap(), Matching::Exact);
+    }
+
+    #[test]
+    #[serial]
+    fn fuzzy_match() {
+        std::env::set_var("_RAD_MATCH", "fuzzy");
+        assert_eq!(rad_match().unwrap(), Matching::Fuzzy);
+        std::env::set_var("_RAD_MATCH", "other");
+        assert!(rad_match().is_err());
+        std::env::remove_var("_RAD_MATCH");
+    }
+
+    #[test]
+    #[serial]
+    fn exported_vars() {
+        assert!(rad_exported_vars().is_empty());
+        std::env::set_var(
+            "_RAD_EXPORTED_VARS",
+            "RAD_A  RAD_B $(rm) 1X PATH _rad_match",
+        );
+        assert_eq!(rad_exported_vars(), vec!["RAD_A", "RAD_B"]);
+        std::env::remove_var("_RAD_EXPORTED_VARS");
+    }
+
+    #[test]
+    #[serial]
+    fn default_env_naming() {
+        assert_eq!(rad_env_prefix().unwrap(), "RAD_");
+        assert_eq!(rad_env_case().unwrap(), EnvCase::Upper);
+    }
+
+    #[test]
+    #[serial]
+    fn env_naming() {
+        std::env::set_var("_RAD_ENV_PREFIX", "PROJ_");
+        std::env::set_var("_RAD_ENV_CASE", "keep");
+        assert_eq!(rad_env_prefix().unwrap(), "PROJ_");
+        assert_eq!(rad_env_case().unwrap(), EnvCase::Keep);
+        std::env::set_var("_RAD_ENV_PREFIX", "");
+        assert_eq!(rad_env_prefix().unwrap(), "");
+        std::env::set_var("_RAD_ENV_PREFIX", "1-");
+        assert!(rad_env_prefix().is_err());
+        std::env::set_var("_RAD_ENV_CASE", "title");
+        assert!(rad_env_case().is_err());
+        std::env::remove_var("_RAD_ENV_PREFIX");
+        std::env::remove_var("_RAD_ENV_CASE");
     }
 }
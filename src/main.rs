@@ -19,7 +19,10 @@ use subcommand::RadSubCmdRunnable;
 const ENV_VAR_HELP: &str = "\x1b[0;31mENVIRONMENT VARIABLES\x1b[0m
     \x1b[0;35m_RAD_ALIASES_DIR\x1b[0m        Directory where configuration is stored
     \x1b[0;35m_RAD_NO_ECHO\x1b[0m            Whether or not to print directory name before cd'ing to it
-    \x1b[0;35m_RAD_RESOLVE_SYMLINKS\x1b[0m   Whether symlinks should be resolved";
+    \x1b[0;35m_RAD_RESOLVE_SYMLINKS\x1b[0m   Whether symlinks should be resolved
+    \x1b[0;35m_RAD_MATCH\x1b[0m              How aliases are matched: exact, prefix or fuzzy
+    \x1b[0;35m_RAD_ENV_PREFIX\x1b[0m         Prefix of alias environment variables, RAD_ by default
+    \x1b[0;35m_RAD_ENV_CASE\x1b[0m           Case of alias environment variable names: upper, lower or keep";
 
 #[derive(Debug, StructOpt)]
 #[structopt(
@@ -58,10 +61,19 @@ pub enum RadSubCmd {
     Remove(subcommand::Remove),
     #[structopt(alias = "rx")]
     RemoveEnv(subcommand::RemoveEnv),
+    #[structopt(alias = "mv")]
+    Rename(subcommand::Rename),
     #[structopt(alias = "res")]
     Resolve(subcommand::Resolve),
     #[structopt(alias = "resx")]
     ResolveEnv(subcommand::ResolveEnv),
+    #[structopt(alias = "s")]
+    Set(subcommand::Set),
+    Stats(subcommand::Stats),
+    Which(subcommand::Which),
+    Abbrev(subcommand::Abbrev),
+    Env(subcommand::Env),
+    ExportEnv(subcommand::ExportEnv),
 
     #[structopt(alias = "comp")]
     Completions(subcommand::Completions),
@@ -71,17 +83,25 @@ fn rad_main() -> Result<String> {
     let opt = Rad::from_args();
 
     let res = match opt.radsubcmd {
+        RadSubCmd::Abbrev(abbrev) => abbrev.run(),
         RadSubCmd::Add(add) => add.run(),
         RadSubCmd::AddEnv(add_env) => add_env.run(),
         RadSubCmd::Completions(completions) => completions.run(),
+        RadSubCmd::Env(env) => env.run(),
+        RadSubCmd::ExportEnv(export_env) => export_env.run(),
         RadSubCmd::Init(init) => init.run(),
         RadSubCmd::List(list) => list.run(),
         RadSubCmd::ListAlias(list_alias) => list_alias.run(),
         RadSubCmd::ListEnv(list_env) => list_env.run(),
         RadSubCmd::Remove(remove) => remove.run(),
         RadSubCmd::RemoveEnv(remove_env) => remove_env.run(),
-        RadSubCmd::Resolve(resolve) => resolve.run(),
+        RadSubCmd::Rename(rename) => rename.run(),
+        // Printed as raw bytes, resolved paths may not be valid UTF-8
+        RadSubCmd::Resolve(resolve) => resolve.resolve().and_then(utils::print_path),
         RadSubCmd::ResolveEnv(resolve_env) => resolve_env.run(),
+        RadSubCmd::Set(set) => set.run(),
+        RadSubCmd::Stats(stats) => stats.run(),
+        RadSubCmd::Which(which) => which.run(),
     };
 
     if res.is_ok() {
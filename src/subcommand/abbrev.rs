@@ -0,0 +1,69 @@
+use crate::config;
+#[cfg(test)]
+use crate::fixture;
+use crate::subcommand::RadSubCmdRunnable;
+use crate::utils;
+use anyhow::{Context, Result};
+use rualdlib::Aliases;
+#[cfg(test)]
+use serial_test::serial;
+use std::path::PathBuf;
+#[cfg(test)]
+use std::str::FromStr;
+use structopt::StructOpt;
+
+/// Abbreviate a path with the alias or %HASH mapping covering most of it
+#[derive(Debug, StructOpt)]
+pub struct Abbrev {
+    /// Path to abbreviate, if not provided current directory is used
+    #[structopt(parse(from_os_str))]
+    pub path: Option<PathBuf>,
+}
+
+impl RadSubCmdRunnable for Abbrev {
+    fn run(&self) -> Result<String> {
+        let aliases_dir = config::rad_aliases_dir().with_context(|| "fail to abbreviate path")?;
+        let mut aliases =
+            Aliases::open_read_only(aliases_dir).with_context(|| "fail to abbreviate path")?;
+
+        let mode = config::rad_path_mode();
+        let path = match &self.path {
+            Some(path) => path.to_owned(),
+            None => utils::get_working_dir(mode)?,
+        };
+        let path = utils::normalize_path(path, mode)?;
+        aliases.set_physical_paths(mode == utils::PathMode::Physical);
+
+        Ok(format!("{}\n", aliases.abbrev(path)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[serial]
+    fn not_covered() {
+        let subcmd = fixture::create_subcmd(Abbrev {
+            path: Some(PathBuf::from_str("/not/aliased").unwrap()),
+        });
+        let res = subcmd.run();
+        assert_eq!(res.unwrap(), "/not/aliased\n");
+    }
+
+    #[test]
+    #[serial]
+    fn alias_and_hash() {
+        let mut subcmd = fixture::create_subcmd(Abbrev {
+            path: Some(PathBuf::from_str("/src/api/./handlers").unwrap()),
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            api = "/src/api"
+            [alias_hash]
+            SRC = "/src"
+        ]);
+        assert_eq!(subcmd.run().unwrap(), "@api/handlers\n");
+    }
+}
@@ -1,10 +1,14 @@
-use crate::{config, ctype_exp, subcommand::RadSubCmdRunnable, utils};
+use crate::{
+    config, ctype_exp,
+    subcommand::RadSubCmdRunnable,
+    utils::{self, PathMode},
+};
 
 #[cfg(test)]
 use crate::fixture;
 use anyhow::{Context, Result};
 use colored::*;
-use rualdlib::Aliases;
+use rualdlib::{AliasEntry, Aliases};
 #[cfg(test)]
 use serial_test::serial;
 use std::path::PathBuf;
@@ -17,8 +21,22 @@ use structopt::StructOpt;
 pub struct Add {
     /// Alias to path
     pub alias: String,
-    /// Path to aliasing, if not provided current directory is used
+    /// Path to aliasing, if not provided current directory is used.
+    /// A path starting with `@alias` is relative to another alias
+    #[structopt(parse(from_os_str))]
     pub path: Option<PathBuf>,
+    /// Description shown when listing and completing aliases
+    #[structopt(short, long)]
+    pub description: Option<String>,
+    /// Tag used to group and filter aliases, can be repeated
+    #[structopt(short, long = "tag", number_of_values = 1)]
+    pub tags: Vec<String>,
+    /// Store the target of symlinks in path, default when _RAD_RESOLVE_SYMLINKS is set
+    #[structopt(short = "P", long, conflicts_with = "logical")]
+    pub physical: bool,
+    /// Store symlinks in path as is
+    #[structopt(short = "L", long)]
+    pub logical: bool,
 }
 
 impl RadSubCmdRunnable for Add {
@@ -39,16 +57,23 @@ impl RadSubCmdRunnable for Add {
         })?;
 
         let path = self.path.to_owned().unwrap_or(utils::get_current_dir()?);
-        let path = utils::resolve_path(path).with_context(|| {
-            format!(
-                "[{}] Failed to add: {}",
-                ctype_exp!("alias"),
-                self.alias.red().bold()
-            )
-        })?;
+        let path = if utils::is_alias_reference(&path) {
+            path
+        } else {
+            utils::resolve_path(path, self.path_mode()).with_context(|| {
+                format!(
+                    "[{}] Failed to add: {}",
+                    ctype_exp!("alias"),
+                    self.alias.red().bold()
+                )
+            })?
+        };
 
+        let entry = AliasEntry::from_path(&path)
+            .with_description(self.description.to_owned())
+            .with_tags(self.tags.to_owned());
         aliases
-            .add(self.alias.to_owned(), utils::path_to_str(&path)?.into())
+            .add_entry(self.alias.to_owned(), entry)
             .with_context(|| {
                 format!(
                     "[{}] Failed to add: {}",
@@ -65,6 +90,18 @@ impl RadSubCmdRunnable for Add {
     }
 }
 
+impl Add {
+    fn path_mode(&self) -> PathMode {
+        if self.physical {
+            PathMode::Physical
+        } else if self.logical {
+            PathMode::Logical
+        } else {
+            config::rad_path_mode()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,6 +112,10 @@ mod tests {
         let subcmd = fixture::create_subcmd(Add {
             alias: String::from("test"),
             path: None,
+            description: None,
+            tags: Vec::new(),
+            physical: false,
+            logical: false,
         });
         let res = subcmd.run();
         assert!(res.is_ok());
@@ -87,18 +128,90 @@ mod tests {
         let subcmd = fixture::create_subcmd(Add {
             alias: String::from("test"),
             path: Some(PathBuf::from_str("not-existing-path").unwrap()),
+            description: None,
+            tags: Vec::new(),
+            physical: false,
+            logical: false,
         });
         let res = subcmd.run();
         assert!(res.is_err());
         assert_eq!(res.unwrap_err().to_string(), "[alias] Failed to add: test");
     }
 
+    #[test]
+    #[serial]
+    fn with_description() {
+        let subcmd = fixture::create_subcmd(Add {
+            alias: String::from("test"),
+            path: None,
+            description: Some(String::from("current directory")),
+            tags: Vec::new(),
+            physical: false,
+            logical: false,
+        });
+        let res = subcmd.run();
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), "[alias] Added: test\n",);
+
+        let aliases = Aliases::open(subcmd.tmp.tmp_dir.path().to_path_buf()).unwrap();
+        let entry = &aliases.aliases.as_ref().unwrap()["test"];
+        assert_eq!(entry.description, Some(String::from("current directory")));
+    }
+
+    #[test]
+    #[serial]
+    fn with_tags() {
+        let subcmd = fixture::create_subcmd(Add {
+            alias: String::from("test"),
+            path: None,
+            description: None,
+            tags: vec![String::from("work"), String::from("rust")],
+            physical: false,
+            logical: false,
+        });
+        let res = subcmd.run();
+        assert!(res.is_ok());
+
+        let aliases = Aliases::open(subcmd.tmp.tmp_dir.path().to_path_buf()).unwrap();
+        let entry = &aliases.aliases.as_ref().unwrap()["test"];
+        assert_eq!(entry.tags, vec![String::from("work"), String::from("rust")]);
+    }
+
+    #[test]
+    #[serial]
+    fn alias_reference() {
+        let mut subcmd = fixture::create_subcmd(Add {
+            alias: String::from("api"),
+            path: Some(PathBuf::from_str("@company/api").unwrap()),
+            description: None,
+            tags: Vec::new(),
+            physical: false,
+            logical: false,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            company = "/src/company"
+        ]);
+        let res = subcmd.run();
+        assert!(res.is_ok());
+
+        let aliases = Aliases::open(subcmd.tmp.tmp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(
+            aliases.get("api").unwrap(),
+            Some(String::from("/src/company/api"))
+        );
+    }
+
     #[test]
     #[serial]
     fn existing_alias() {
         let mut subcmd = fixture::create_subcmd(Add {
             alias: String::from("test"),
             path: None,
+            description: None,
+            tags: Vec::new(),
+            physical: false,
+            logical: false,
         });
         subcmd.use_config(toml::toml![
             [aliases]
@@ -108,4 +221,32 @@ mod tests {
         assert!(res.is_err());
         assert_eq!(res.unwrap_err().to_string(), "[alias] Failed to add: test");
     }
+
+    #[cfg(unix)]
+    #[test]
+    #[serial]
+    fn physical_and_logical() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = std::fs::canonicalize(dir.path()).unwrap().join("target");
+        let link = target.with_file_name("link");
+        std::fs::create_dir(&target).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        for (physical, expected) in [(true, &target), (false, &link)] {
+            let subcmd = fixture::create_subcmd(Add {
+                alias: String::from("test"),
+                path: Some(link.clone()),
+                description: None,
+                tags: Vec::new(),
+                physical,
+                logical: !physical,
+            });
+            assert!(subcmd.run().is_ok());
+            let aliases = Aliases::open(subcmd.tmp.tmp_dir.path().to_path_buf()).unwrap();
+            assert_eq!(
+                aliases.get("test").unwrap(),
+                Some(expected.to_str().unwrap().into())
+            );
+        }
+    }
 }
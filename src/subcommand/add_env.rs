@@ -5,23 +5,37 @@ use crate::fixture;
 use crate::subcommand::RadSubCmdRunnable;
 use anyhow::{anyhow, Context, Result};
 use colored::*;
-use rualdlib::Aliases;
+use rualdlib::{Aliases, EnvEntry};
 #[cfg(test)]
 use serial_test::serial;
 use structopt::StructOpt;
 
-/// Add new environment variable for an alias
+/// Add new environment variable for an alias, an alias can have several
 #[derive(Debug, StructOpt)]
 pub struct AddEnv {
-    /// Alias to link
+    /// Alias to link, may be followed by a subpath as `alias/sub`
     pub alias: String,
     /// Environment variable to link on alias, if not provided alias is used
     pub var: Option<String>,
+    /// Export the variable as is, without the configured prefix and case
+    #[structopt(long)]
+    pub no_prefix: bool,
 }
 
 impl RadSubCmdRunnable for AddEnv {
     fn run(&self) -> Result<String> {
-        let var = self.var.as_ref().unwrap_or(&self.alias).to_uppercase();
+        let entry = EnvEntry::new(self.alias.trim_end_matches('/')).with_prefix(!self.no_prefix);
+        let var = match &self.var {
+            Some(var) => var.to_owned(),
+            None => entry.target.replace('/', "_"),
+        };
+        let var = if entry.prefixed {
+            config::rad_env_case()
+                .with_context(|| format!("Failed to add: [{}]", ctype_exp!("env")))?
+                .apply(&var)
+        } else {
+            var
+        };
 
         let aliases_dir = config::rad_aliases_dir().with_context(|| {
             format!(
@@ -42,7 +56,7 @@ impl RadSubCmdRunnable for AddEnv {
             )
         })?;
 
-        if aliases.get(&self.alias).is_none() {
+        if !aliases.contains(entry.alias()) {
             return Err(anyhow!(format!(
                 "[{}] {} doesn't exist. Cannot add [{}] {}",
                 ctype_exp!("alias"),
@@ -51,8 +65,9 @@ impl RadSubCmdRunnable for AddEnv {
                 var.red().bold()
             )));
         }
+        config::set_env_naming(&mut aliases)?;
         aliases
-            .add_env(self.alias.to_owned(), var.to_owned())
+            .add_env_entry(var.to_owned(), entry)
             .with_context(|| {
                 format!(
                     "Failed to add: [{}] {} for [{}] {}",
@@ -83,6 +98,7 @@ mod tests {
         let subcmd = fixture::create_subcmd(AddEnv {
             alias: String::from("test"),
             var: None,
+            no_prefix: false,
         });
         let res = subcmd.run();
         assert!(res.is_err());
@@ -99,6 +115,7 @@ mod tests {
         let subcmd = fixture::create_subcmd(AddEnv {
             alias: String::from("test"),
             var: Some(String::from("PROVIDED")),
+            no_prefix: false,
         });
         let res = subcmd.run();
         assert!(res.is_err());
@@ -115,6 +132,7 @@ mod tests {
         let mut subcmd = fixture::create_subcmd(AddEnv {
             alias: String::from("test"),
             var: None,
+            no_prefix: false,
         });
         subcmd.use_config(toml::toml!(
             [aliases]
@@ -132,6 +150,7 @@ mod tests {
         let mut subcmd = fixture::create_subcmd(AddEnv {
             alias: String::from("test"),
             var: Some(String::from("PROVIDED")),
+            no_prefix: false,
         });
         subcmd.use_config(toml::toml!(
             [aliases]
@@ -149,6 +168,7 @@ mod tests {
         let mut subcmd = fixture::create_subcmd(AddEnv {
             alias: String::from("test"),
             var: Some(String::from("PROVIDED")),
+            no_prefix: false,
         });
         subcmd.use_config(toml::toml!(
             [aliases]
@@ -157,11 +177,39 @@ mod tests {
             test = "TEST"
         ));
         let res = subcmd.run();
-        assert!(res.is_err());
-        assert_eq!(
-            res.unwrap_err().to_string(),
-            "Failed to add: [env] PROVIDED for [alias] test"
-        );
+        assert_eq!(res.unwrap(), "[env] PROVIDED added for [alias] test");
+    }
+
+    #[test]
+    #[serial]
+    fn subpath_without_var() {
+        let mut subcmd = fixture::create_subcmd(AddEnv {
+            alias: String::from("test/src/"),
+            var: None,
+            no_prefix: false,
+        });
+        subcmd.use_config(toml::toml!(
+            [aliases]
+            test = "test"
+        ));
+        let res = subcmd.run();
+        assert_eq!(res.unwrap(), "[env] TEST_SRC added for [alias] test/src/");
+    }
+
+    #[test]
+    #[serial]
+    fn no_prefix() {
+        let mut subcmd = fixture::create_subcmd(AddEnv {
+            alias: String::from("test"),
+            var: Some(String::from("GoPath")),
+            no_prefix: true,
+        });
+        subcmd.use_config(toml::toml!(
+            [aliases]
+            test = "test"
+        ));
+        let res = subcmd.run();
+        assert_eq!(res.unwrap(), "[env] GoPath added for [alias] test");
     }
 
     #[test]
@@ -170,6 +218,7 @@ mod tests {
         let mut subcmd = fixture::create_subcmd(AddEnv {
             alias: String::from("test2"),
             var: Some(String::from("TEST")),
+            no_prefix: false,
         });
         subcmd.use_config(toml::toml!(
             [aliases]
@@ -192,6 +241,7 @@ mod tests {
         let mut subcmd = fixture::create_subcmd(AddEnv {
             alias: String::from("test"),
             var: Some(String::from("provided")),
+            no_prefix: false,
         });
         subcmd.use_config(toml::toml!(
             [aliases]
@@ -202,4 +252,22 @@ mod tests {
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), "[env] PROVIDED added for [alias] test");
     }
+
+    #[test]
+    #[serial]
+    fn keeps_case() {
+        std::env::set_var("_RAD_ENV_CASE", "keep");
+        let mut subcmd = fixture::create_subcmd(AddEnv {
+            alias: String::from("test"),
+            var: Some(String::from("MyVar")),
+            no_prefix: false,
+        });
+        subcmd.use_config(toml::toml!(
+            [aliases]
+            test = "test"
+        ));
+        let res = subcmd.run();
+        std::env::remove_var("_RAD_ENV_CASE");
+        assert_eq!(res.unwrap(), "[env] MyVar added for [alias] test");
+    }
 }
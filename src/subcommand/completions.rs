@@ -40,6 +40,9 @@ pub struct Completions {
         required_if("comp_type", "shell"),
     )]
     shell: Option<ShellType>,
+    /// Only list aliases carrying this tag, can be repeated
+    #[structopt(short, long = "tag", number_of_values = 1)]
+    tags: Vec<String>,
 }
 
 arg_enum! {
@@ -83,7 +86,7 @@ impl RadSubCmdRunnable for Completions {
 
         let res = match self.comp_type {
             CompType::alias => aliases
-                .list_alias_completions()
+                .list_alias_completions(&self.tags)
                 .unwrap_or_else(|| "None".into()),
             CompType::env => aliases
                 .list_env_completions()
@@ -0,0 +1,387 @@
+use crate::config;
+#[cfg(test)]
+use crate::fixture;
+use crate::subcommand::RadSubCmdRunnable;
+use crate::utils;
+use anyhow::{Context, Result};
+use colored::*;
+use rualdlib::{Aliases, EnvEntry, Error};
+#[cfg(test)]
+use serial_test::serial;
+use structopt::clap::arg_enum;
+use structopt::StructOpt;
+
+/// Print statements exporting the environment variables of every alias
+#[derive(Debug, StructOpt)]
+pub struct Env {
+    /// Shell syntax of the statements
+    #[structopt(
+        long,
+        possible_values = &Shell::variants(),
+        case_insensitive = true,
+        default_value = "posix"
+    )]
+    pub shell: Shell,
+    /// Unset the variables exported by a previous `--track` call which are
+    /// not exported anymore, exported names are recorded in the shell variable
+    /// `_RAD_EXPORTED_VARS`, which is not exported and must be passed to rualdi
+    #[structopt(long)]
+    pub track: bool,
+}
+
+impl RadSubCmdRunnable for Env {
+    fn run(&self) -> Result<String> {
+        let aliases_dir =
+            config::rad_aliases_dir().with_context(|| "fail to export environment variables")?;
+        let mut aliases =
+            Aliases::open(aliases_dir).with_context(|| "fail to export environment variables")?;
+        config::set_env_naming(&mut aliases)?;
+
+        let vars = resolve_vars(&aliases, aliases.vars.clone().unwrap_or_default())?;
+        let names = vars
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        let mut res = String::new();
+        if self.track {
+            for name in config::rad_exported_vars() {
+                if !names.contains(&name.as_str()) {
+                    res.push_str(&self.unset(&name));
+                }
+            }
+        }
+        for (name, value) in vars.iter() {
+            res.push_str(&self.export(name, value));
+        }
+        if self.track {
+            res.push_str(&self.assign("_RAD_EXPORTED_VARS", names.join(" ").as_bytes()));
+        }
+
+        Ok(res)
+    }
+}
+
+/// Resolve variables to the names they are exported as and the paths they
+/// point on, invalid names and broken aliases are reported and skipped
+pub(crate) fn resolve_vars<I>(aliases: &Aliases, vars: I) -> Result<Vec<(String, Vec<u8>)>>
+where
+    I: IntoIterator<Item = (String, EnvEntry)>,
+{
+    let mode = config::rad_path_mode();
+
+    let mut res = Vec::new();
+    for (var, entry) in vars {
+        let name = aliases.env_name(&var, entry.prefixed);
+        // The reason is followed by the valid name suggested by `list`
+        if let Err(e) = aliases.check_env_var(&var, entry.prefixed) {
+            let reason = match e {
+                Error::InvalidName { reason, .. } => reason,
+                e => e.to_string(),
+            };
+            eprintln!(
+                "[{}] Invalid environment variable name {}, not exported: {}",
+                ctype_exp!("env"),
+                name.red().bold(),
+                reason
+            );
+            continue;
+        }
+        let path = aliases
+            .get_env_path(&entry)
+            .map_err(anyhow::Error::from)
+            .and_then(|path| path.with_context(|| format!("alias not found: {}", entry.alias())))
+            .and_then(|path| utils::resolve_path(path, mode));
+        match path {
+            Ok(path) => res.push((name, utils::path_bytes(path))),
+            // A broken alias must not prevent the others from being exported
+            Err(e) => eprintln!(
+                "[{}] Alias {} is broken, {} not exported: {:#}",
+                ctype_exp!("env"),
+                entry.target.red().bold(),
+                name,
+                e
+            ),
+        }
+    }
+
+    Ok(res)
+}
+
+/// Get the name var is exported as for shell functions which unset it, names
+/// which could not have been exported, such as `PATH`, are reported and
+/// skipped so that they are never unset
+pub(crate) fn checked_env_name(aliases: &Aliases, var: &str, prefixed: bool) -> Option<String> {
+    let name = aliases.env_name(var, prefixed);
+    match Aliases::check_env_name(&name) {
+        Ok(()) => Some(name),
+        Err(e) => {
+            eprintln!(
+                "[{}] Skipped {}: {}",
+                ctype_exp!("env"),
+                name.red().bold(),
+                e
+            );
+            None
+        }
+    }
+}
+
+impl Env {
+    /// Get the statement setting var to value
+    fn export(&self, var: &str, value: &[u8]) -> String {
+        match self.shell {
+            Shell::fish => format!("set -gx {} {}\n", var, quote(value, &self.shell)),
+            _ => format!("export {}={}\n", var, quote(value, &self.shell)),
+        }
+    }
+
+    /// Get the statement setting var to value in the shell only, global
+    /// even when evaluated in a function
+    fn assign(&self, var: &str, value: &[u8]) -> String {
+        match self.shell {
+            Shell::fish => format!("set -g {} {}\n", var, quote(value, &self.shell)),
+            Shell::zsh => format!("typeset -g {}={}\n", var, quote(value, &self.shell)),
+            _ => format!("{}={}\n", var, quote(value, &self.shell)),
+        }
+    }
+
+    /// Get the statement removing var
+    fn unset(&self, var: &str) -> String {
+        match self.shell {
+            Shell::fish => format!("set -e {}\n", var),
+            _ => format!("unset {}\n", var),
+        }
+    }
+}
+
+arg_enum! {
+    #[allow(non_camel_case_types)]
+    #[derive(Debug)]
+    pub enum Shell {
+        bash,
+        zsh,
+        posix,
+        fish,
+    }
+}
+
+/// Quote value so that shell reads it back byte for byte, bytes which are
+/// not valid UTF-8 are written as escapes
+pub(crate) fn quote(value: &[u8], shell: &Shell) -> String {
+    match (std::str::from_utf8(value), shell) {
+        (Ok(value), Shell::fish) => {
+            format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+        }
+        (Ok(value), _) => format!("'{}'", value.replace('\'', "'\\''")),
+        (Err(_), Shell::bash) | (Err(_), Shell::zsh) => {
+            format!("$'{}'", escape(value, |b| format!("\\x{:02x}", b)))
+        }
+        // Command substitution drops trailing newlines, they are
+        // appended in single quotes
+        (Err(_), Shell::posix) => {
+            let end = value.iter().rposition(|&b| b != b'\n').map_or(0, |i| i + 1);
+            let mut res = format!(
+                "\"$(printf '{}')\"",
+                escape(&value[..end], |b| format!("\\{:03o}", b))
+            );
+            if end < value.len() {
+                res.push_str(&format!("'{}'", "\n".repeat(value.len() - end)));
+            }
+            res
+        }
+        (Err(_), Shell::fish) => escape(value, |b| format!("\\X{:02x}", b)),
+    }
+}
+
+/// Keep the bytes which never need quoting, escape the others
+fn escape(value: &[u8], escape_byte: impl Fn(u8) -> String) -> String {
+    value
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_alphanumeric() || b"/._-".contains(&b) {
+                (b as char).to_string()
+            } else {
+                escape_byte(b)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[serial]
+    fn no_vars() {
+        let subcmd = fixture::create_subcmd(Env {
+            shell: Shell::posix,
+            track: false,
+        });
+        let res = subcmd.run();
+        assert_eq!(res.unwrap(), "");
+    }
+
+    #[test]
+    #[serial]
+    fn skips_broken_aliases() {
+        let mut subcmd = fixture::create_subcmd(Env {
+            shell: Shell::bash,
+            track: false,
+        });
+        let dir = subcmd.tmp.tmp_dir.path().to_path_buf();
+        subcmd.use_config(
+            toml::from_str(&format!(
+                r#"
+                [aliases]
+                config = "{}"
+                gone = "/not/existing/path"
+                [environment]
+                config = "config"
+                gone = "GONE"
+                "#,
+                dir.display()
+            ))
+            .unwrap(),
+        );
+        let res = subcmd.run();
+        assert_eq!(
+            res.unwrap(),
+            format!("export RAD_CONFIG='{}'\n", dir.display())
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn subpaths_and_no_prefix() {
+        let mut subcmd = fixture::create_subcmd(Env {
+            shell: Shell::bash,
+            track: false,
+        });
+        let dir = subcmd.tmp.tmp_dir.path().to_path_buf();
+        std::fs::create_dir(dir.join("src")).unwrap();
+        subcmd.use_config(
+            toml::from_str(&format!(
+                r#"
+                version = 6
+                [aliases]
+                config = "{}"
+                [environment]
+                CONFIG = "config"
+                CONFIG_SRC = "config/src"
+                CONFIG_ROOT = {{ alias = "config", prefix = false }}
+                "#,
+                dir.display()
+            ))
+            .unwrap(),
+        );
+        assert_eq!(
+            subcmd.run().unwrap(),
+            format!(
+                "export RAD_CONFIG='{0}'\nexport CONFIG_ROOT='{0}'\nexport RAD_CONFIG_SRC='{0}/src'\n",
+                dir.display()
+            )
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn track() {
+        let mut subcmd = fixture::create_subcmd(Env {
+            shell: Shell::bash,
+            track: true,
+        });
+        let dir = subcmd.tmp.tmp_dir.path().to_path_buf();
+        subcmd.use_config(
+            toml::from_str(&format!(
+                r#"
+                version = 6
+                [aliases]
+                config = "{}"
+                [environment]
+                CONFIG = "config"
+                CONFIG_ROOT = {{ alias = "config", prefix = false }}
+                "#,
+                dir.display()
+            ))
+            .unwrap(),
+        );
+        std::env::set_var("_RAD_EXPORTED_VARS", "RAD_CONFIG RAD_GONE");
+        let res = subcmd.run();
+        std::env::remove_var("_RAD_EXPORTED_VARS");
+        assert_eq!(
+            res.unwrap(),
+            format!(
+                "unset RAD_GONE\nexport RAD_CONFIG='{0}'\nexport CONFIG_ROOT='{0}'\n\
+                 _RAD_EXPORTED_VARS='RAD_CONFIG CONFIG_ROOT'\n",
+                dir.display()
+            )
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn skips_invalid_names() {
+        let mut subcmd = fixture::create_subcmd(Env {
+            shell: Shell::bash,
+            track: false,
+        });
+        let dir = subcmd.tmp.tmp_dir.path().to_path_buf();
+        subcmd.use_config(
+            toml::from_str(&format!(
+                r#"
+                [aliases]
+                config = "{}"
+                [environment]
+                config = "my-config"
+                "#,
+                dir.display()
+            ))
+            .unwrap(),
+        );
+        assert_eq!(subcmd.run().unwrap(), "");
+    }
+
+    #[test]
+    fn quoting() {
+        let value = b"/it's a \\path";
+        assert_eq!(quote(value, &Shell::bash), r"'/it'\''s a \path'");
+        assert_eq!(quote(value, &Shell::posix), r"'/it'\''s a \path'");
+        assert_eq!(quote(value, &Shell::fish), r"'/it\'s a \\path'");
+    }
+
+    #[test]
+    fn quoting_not_unicode() {
+        let value = b"/caf\xe9 x";
+        assert_eq!(quote(value, &Shell::zsh), r"$'/caf\xe9\x20x'");
+        assert_eq!(
+            quote(value, &Shell::posix),
+            r#""$(printf '/caf\351\040x')""#
+        );
+        assert_eq!(quote(value, &Shell::fish), r"/caf\Xe9\X20x");
+        assert_eq!(
+            quote(b"/caf\xe9\n", &Shell::posix),
+            "\"$(printf '/caf\\351')\"'\n'"
+        );
+    }
+
+    #[test]
+    fn statements() {
+        let env = Env {
+            shell: Shell::fish,
+            track: false,
+        };
+        assert_eq!(env.export("RAD_X", b"/x"), "set -gx RAD_X '/x'\n");
+        assert_eq!(env.assign("RAD_X", b"/x"), "set -g RAD_X '/x'\n");
+        let env = Env {
+            shell: Shell::zsh,
+            track: false,
+        };
+        assert_eq!(env.assign("RAD_X", b"/x"), "typeset -g RAD_X='/x'\n");
+        let env = Env {
+            shell: Shell::posix,
+            track: false,
+        };
+        assert_eq!(env.export("RAD_X", b"/x"), "export RAD_X='/x'\n");
+    }
+}
@@ -0,0 +1,259 @@
+use crate::config;
+#[cfg(test)]
+use crate::fixture;
+use crate::subcommand::env::{quote, resolve_vars, Shell};
+use crate::subcommand::RadSubCmdRunnable;
+use anyhow::{anyhow, Context, Result};
+use colored::*;
+use rualdlib::{Aliases, EnvEntry};
+#[cfg(test)]
+use serial_test::serial;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use structopt::clap::arg_enum;
+use structopt::StructOpt;
+
+/// First line of exported files, the content must not depend on
+/// the time of the export so that unchanged files are not rewritten
+const HEADER: &str = "# Generated by `rualdi export-env`, changes will be overwritten\n";
+
+/// Write the environment variables of aliases to a file read by tools which
+/// do not run the shell init, such as systemd user units or docker-compose
+#[derive(Debug, StructOpt)]
+pub struct ExportEnv {
+    /// Syntax of the file: dotenv, systemd environment.d or direnv .envrc
+    #[structopt(
+        long,
+        possible_values = &Format::variants(),
+        case_insensitive = true,
+        default_value = "dotenv"
+    )]
+    pub format: Format,
+    /// Also export every alias, named as its environment variable would be
+    #[structopt(short, long)]
+    pub all: bool,
+    /// File to write, only touched when its content changes, standard
+    /// output if not provided
+    #[structopt(short, long, parse(from_os_str))]
+    pub output: Option<PathBuf>,
+}
+
+impl RadSubCmdRunnable for ExportEnv {
+    fn run(&self) -> Result<String> {
+        let aliases_dir =
+            config::rad_aliases_dir().with_context(|| "fail to export environment variables")?;
+        let mut aliases =
+            Aliases::open(aliases_dir).with_context(|| "fail to export environment variables")?;
+        config::set_env_naming(&mut aliases)?;
+
+        // Variables of `[environment]` win over the ones named after aliases
+        let mut vars = BTreeMap::new();
+        if self.all {
+            let case = config::rad_env_case()?;
+            let entries = aliases
+                .aliases
+                .iter()
+                .flatten()
+                .map(|(alias, _)| (case.apply(alias), EnvEntry::new(alias.as_str())))
+                .collect::<Vec<_>>();
+            vars.extend(resolve_vars(&aliases, entries)?);
+        }
+        vars.extend(resolve_vars(
+            &aliases,
+            aliases.vars.clone().unwrap_or_default(),
+        )?);
+
+        let mut content = String::from(HEADER);
+        for (name, value) in vars {
+            match self.format.assignment(&name, &value) {
+                Ok(line) => content.push_str(&line),
+                Err(e) => eprintln!(
+                    "[{}] {} not exported: {:#}",
+                    ctype_exp!("env"),
+                    name.red().bold(),
+                    e
+                ),
+            }
+        }
+
+        let path = match &self.output {
+            Some(path) => path,
+            None => return Ok(content),
+        };
+        if fs::read(path).is_ok_and(|old| old == content.as_bytes()) {
+            return Ok(format!(
+                "[{}] Unchanged: {}\n",
+                ctype_exp!("env"),
+                path.display()
+            ));
+        }
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("could not create directory: {}", parent.display()))?;
+        }
+        rualdlib::write_atomic(path, content.as_bytes())
+            .with_context(|| format!("could not write file: {}", path.display()))?;
+
+        Ok(format!(
+            "[{}] Written: {}\n",
+            ctype_exp!("env"),
+            path.display()
+        ))
+    }
+}
+
+arg_enum! {
+    #[allow(non_camel_case_types)]
+    #[derive(Debug)]
+    pub enum Format {
+        dotenv,
+        systemd,
+        direnv,
+    }
+}
+
+impl Format {
+    /// Get the line setting var to value, only direnv can hold
+    /// values which are not valid UTF-8
+    fn assignment(&self, var: &str, value: &[u8]) -> Result<String> {
+        if let Format::direnv = self {
+            return Ok(format!("export {}={}\n", var, quote(value, &Shell::bash)));
+        }
+        let value = std::str::from_utf8(value).map_err(|_| anyhow!("path is not valid UTF-8"))?;
+        let value = if value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"/._-".contains(&b))
+        {
+            value.to_owned()
+        } else {
+            match self {
+                // Single quotes are read literally by docker-compose and
+                // python-dotenv, but cannot be escaped in them, docker-compose
+                // interpolates `$` in double quotes unless it is doubled
+                Format::dotenv if !value.contains('\'') => format!("'{}'", value),
+                Format::dotenv => format!("\"{}\"", escape(value, "\\\"").replace('$', "$$")),
+                // environment.d expands `$` even in double quotes
+                _ => format!("\"{}\"", escape(value, "\\\"$`")),
+            }
+        };
+        Ok(format!("{}={}\n", var, value))
+    }
+}
+
+/// Prefix with a backslash the characters of value found in special
+fn escape(value: &str, special: &str) -> String {
+    let mut res = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(c) {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_subcmd(
+        format: Format,
+        all: bool,
+        output: Option<PathBuf>,
+    ) -> fixture::TestSubCmd<ExportEnv> {
+        let mut subcmd = fixture::create_subcmd(ExportEnv {
+            format,
+            all,
+            output,
+        });
+        let dir = subcmd.tmp.tmp_dir.path().to_path_buf();
+        subcmd.use_config(
+            toml::from_str(&format!(
+                r#"
+                version = 6
+                [aliases]
+                config = "{}"
+                gone = "/not/existing/path"
+                [environment]
+                CONFIG_DIR = "config"
+                GONE = "gone"
+                "#,
+                dir.display()
+            ))
+            .unwrap(),
+        );
+        subcmd
+    }
+
+    #[test]
+    #[serial]
+    fn environment() {
+        let subcmd = create_subcmd(Format::dotenv, false, None);
+        let dir = subcmd.tmp.tmp_dir.path().display().to_string();
+        assert_eq!(
+            subcmd.run().unwrap(),
+            format!("{}RAD_CONFIG_DIR={}\n", HEADER, dir)
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn every_alias() {
+        let subcmd = create_subcmd(Format::systemd, true, None);
+        let dir = subcmd.tmp.tmp_dir.path().display().to_string();
+        assert_eq!(
+            subcmd.run().unwrap(),
+            format!("{}RAD_CONFIG={1}\nRAD_CONFIG_DIR={1}\n", HEADER, dir)
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn output_only_written_on_change() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let output = out_dir.path().join("project/.envrc");
+        let subcmd = create_subcmd(Format::direnv, false, Some(output.clone()));
+        let dir = subcmd.tmp.tmp_dir.path().display().to_string();
+        assert_eq!(
+            subcmd.run().unwrap(),
+            format!("[env] Written: {}\n", output.display())
+        );
+        let modified = fs::metadata(&output).unwrap().modified().unwrap();
+        assert_eq!(
+            subcmd.run().unwrap(),
+            format!("[env] Unchanged: {}\n", output.display())
+        );
+        assert_eq!(fs::metadata(&output).unwrap().modified().unwrap(), modified);
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            format!("{}export RAD_CONFIG_DIR='{}'\n", HEADER, dir)
+        );
+    }
+
+    #[test]
+    fn quoting() {
+        let value = b"/it's $HOME";
+        assert_eq!(
+            Format::dotenv.assignment("X", b"/src/api").unwrap(),
+            "X=/src/api\n"
+        );
+        assert_eq!(
+            Format::dotenv.assignment("X", b"/my api").unwrap(),
+            "X='/my api'\n"
+        );
+        assert_eq!(
+            Format::dotenv.assignment("X", value).unwrap(),
+            "X=\"/it's $$HOME\"\n"
+        );
+        assert_eq!(
+            Format::systemd.assignment("X", value).unwrap(),
+            "X=\"/it's \\$HOME\"\n"
+        );
+        assert_eq!(
+            Format::direnv.assignment("X", value).unwrap(),
+            "export X='/it'\\''s $HOME'\n"
+        );
+        assert!(Format::systemd.assignment("X", b"/caf\xe9").is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use super::Init;
 use crate::config;
+use crate::subcommand::env::Shell;
 
 use anyhow::Result;
 use std::io::Write;
@@ -30,6 +31,48 @@ __rualdi_cd() {
 }"#
     };
 
+    let __rualdi_env_hook = if options.env_hook {
+        format!(
+            r#"# Reconcile environment variables with the configuration file before each
+# prompt following a change of the file, variables changed or removed from
+# another shell are updated. The modification time and size of the file
+# are recorded in `__rualdi_env_stamp`.
+__rualdi_env_hook() {{
+    local __rualdi_ret=$? __rualdi_stamp
+    __rualdi_stamp="$(stat -c '%Y %s' -- {file} 2>/dev/null || stat -f '%m %z' -- {file} 2>/dev/null)"
+    if [[ $__rualdi_stamp != "${{__rualdi_env_stamp-}}" ]]; then
+        __rualdi_env_stamp=$__rualdi_stamp
+        eval "$(_RAD_EXPORTED_VARS="${{_RAD_EXPORTED_VARS-}}" rualdi env --shell bash --track 2>/dev/null)"
+    fi
+    return $__rualdi_ret
+}}
+if [[ ${{PROMPT_COMMAND:=}} != *__rualdi_env_hook* ]]; then
+    PROMPT_COMMAND="__rualdi_env_hook;${{PROMPT_COMMAND#;}}"
+fi
+"#,
+            file = options.aliases_file(&Shell::bash)?
+        )
+    } else {
+        String::new()
+    };
+
+    let __rualdi_prompt = if options.prompt {
+        r#"# =============================================================================
+# Prompt segment, `$rualdi_prompt` holds the current directory abbreviated
+# with aliases and %HASH mappings, use it in PS1 as `${rualdi_prompt}`.
+__rualdi_prompt_hook() {
+    local __rualdi_ret=$?
+    rualdi_prompt="$(rualdi abbrev -- "$PWD" 2>/dev/null)" || rualdi_prompt="${PWD/#$HOME/\~}"
+    return $__rualdi_ret
+}
+if [[ ${PROMPT_COMMAND:=} != *__rualdi_prompt_hook* ]]; then
+    PROMPT_COMMAND="__rualdi_prompt_hook;${PROMPT_COMMAND#;}"
+fi
+"#
+    } else {
+        ""
+    };
+
     let aliases = format!(
         r#"
 alias {cmd}='__rualdi_rad'
@@ -39,6 +82,7 @@ alias {cmd}a='__rualdi_rada'
 alias {cmd}ax='__rualdi_radax'
 alias {cmd}l='__rualdi_radl'
 alias {cmd}r='__rualdi_radr'
+alias {cmd}mv='__rualdi_radmv'
 alias {cmd}xr='__rualdi_radxr'"#,
         cmd = options.cmd
     );
@@ -54,9 +98,21 @@ alias {cmd}xr='__rualdi_radxr'"#,
 {__rualdi_pwd}
 # cd + custom logic + resolving based on the value of _RAD_NO_ECHO.
 {__rualdi_cd}
+# Naming of alias environment variables, from `--env-prefix` and `--env-case`
+# or the values of _RAD_ENV_PREFIX and _RAD_ENV_CASE.
+{env_naming}
+# Get the name the environment variable of an alias is exported as.
+__rualdi_env_name() {{
+    case "$_RAD_ENV_CASE" in
+        upper) echo "$_RAD_ENV_PREFIX${{1^^}}" ;;
+        lower) echo "$_RAD_ENV_PREFIX${{1,,}}" ;;
+        *) echo "$_RAD_ENV_PREFIX$1" ;;
+    esac
+}}
 # =============================================================================
 #
-# Jump to a directory using alias.
+# Jump to a directory using alias, `--mkdir`, `--no-check` and `--ancestor` are
+# forwarded to `rualdi resolve`.
 __rualdi_rad() {{
     if [ "$#" -eq 0 ]; then
         __rualdi_cd ~
@@ -68,8 +124,17 @@ __rualdi_rad() {{
             return 1
         fi
     else
-        local __rualdi_result
-        __rualdi_result="$(rualdi resolve -- "$@")" && __rualdi_cd "$__rualdi_result"
+        local __rualdi_result __rualdi_arg
+        local -a __rualdi_opts=() __rualdi_args=()
+        # Forward resolve flags, everything else is the path to resolve
+        for __rualdi_arg in "$@"; do
+            case "$__rualdi_arg" in
+                --mkdir|--no-check|--ancestor) __rualdi_opts+=("$__rualdi_arg") ;;
+                *) __rualdi_args+=("$__rualdi_arg") ;;
+            esac
+        done
+        __rualdi_result="$(rualdi resolve --record "${{__rualdi_opts[@]}}" -- "${{__rualdi_args[@]}}")" && \
+        __rualdi_cd "$__rualdi_result"
     fi
 }}
 # Add a new alias to the rualdi aliases configuration file.
@@ -78,34 +143,49 @@ __rualdi_rada() {{
 }}
 # Add a new alias environment variable to the current environment and fill rualdi aliases configuration file.
 __rualdi_radx() {{
-    local __rualdi_alias=$1 __rualdi_var
     rualdi add-env -- "$@" && \
-    __rualdi_var="$(rualdi resolve-env -- $__rualdi_alias)" && \
-    export RAD_$__rualdi_var="$(rualdi resolve -- "$__rualdi_alias")" && \
-    echo "Environment variable 'RAD_$__rualdi_var' added to current environment"
+    eval "$(rualdi env --shell bash --track)"
 }}
 # Add a new alias environment variable to the current environment without filling rualdi aliases configuration file.
 __rualdi_radxn() {{
     local __rualdi_alias=$1 __rualdi_var=$2
-    test "$__rualdi_var" && __rualdi_var=${{__rualdi_var^^}} || __rualdi_var=${{__rualdi_alias^^}}
-    export RAD_$__rualdi_var="$(rualdi resolve -- "$__rualdi_alias")" && \
-    echo "Environment variable 'RAD_$__rualdi_var' added to current environment without filling rualdi alias configuration file"
+    __rualdi_var="$(__rualdi_env_name "${{__rualdi_var:-${{__rualdi_alias//\//_}}}}")"
+    export $__rualdi_var="$(rualdi resolve -- "$__rualdi_alias")" && \
+    echo "Environment variable '$__rualdi_var' added to current environment without filling rualdi alias configuration file"
 }}
 # Add a new alias to the rualdi aliases configuration file with environment variable associated.
 __rualdi_radax() {{
-    local __rualdi_alias=$1 __rualdi_path=$2 __rualdi_var=$3 __rualdi_get
+    local __rualdi_alias=$1 __rualdi_path=$2 __rualdi_var=$3
     rualdi add -- $__rualdi_alias $__rualdi_path && \
     rualdi add-env -- $__rualdi_alias $__rualdi_var && \
-    __rualdi_get="$(rualdi resolve-env -- $__rualdi_alias)" && \
-    export RAD_$__rualdi_get=$(rualdi resolve -- $__rualdi_alias)
+    eval "$(rualdi env --shell bash --track)"
 }}
-# Remove an alias to the rualdi aliases configuration file.
+# Remove an alias to the rualdi aliases configuration file and unset its environment variables.
 __rualdi_radr() {{
-    rualdi remove -- "$@"
+    local __rualdi_vars __rualdi_ret
+    __rualdi_vars="$(rualdi remove --porcelain -- "$@")"
+    __rualdi_ret=$?
+    [ -n "$__rualdi_vars" ] && unset $__rualdi_vars
+    return $__rualdi_ret
+}}
+# Rename an alias in the rualdi aliases configuration file and update its environment variables in the current environment.
+__rualdi_radmv() {{
+    local __rualdi_old_vars
+    __rualdi_old_vars="$(rualdi resolve-env --exported -- "$1" 2>/dev/null)"
+    rualdi rename -- "$@" || return
+    [ -n "$__rualdi_old_vars" ] && {{
+        unset $__rualdi_old_vars
+        eval "$(rualdi env --shell bash --track)"
+    }}
+    return 0
 }}
-# Remove an alias environment variable to the rualdi aliases configuration file.
+# Remove alias environment variables to the rualdi aliases configuration file and unset them.
 __rualdi_radxr() {{
-    rualdi remove-env -- "$@"
+    local __rualdi_vars __rualdi_ret
+    __rualdi_vars="$(rualdi remove-env --porcelain -- "$@")"
+    __rualdi_ret=$?
+    [ -n "$__rualdi_vars" ] && unset $__rualdi_vars
+    return $__rualdi_ret
 }}
 
 # List aliases and alias environment variables to the rualdi aliases configuration file.
@@ -115,18 +195,8 @@ __rualdi_radl() {{
 # =============================================================================
 #
 # Restore environment variables
-OLDIFS="$IFS"
-IFS=$'\n'
-for line in $(rualdi list-env); do
-    local __rualdi_alias __rualdi_path __rualdi__var
-    __rualdi_alias=$(echo $line | cut -d' ' -f1)
-    __rualdi_var=$(echo $line | cut -d' ' -f2)
-    __rualdi_path=$(rualdi resolve -- $__rualdi_alias)
-    export RAD_${{__rualdi_var^^}}="$__rualdi_path"
-done
-IFS="$OLDIFS"
-unset OLDIFS
-# =============================================================================
+eval "$(rualdi env --shell bash --track)"
+{__rualdi_env_hook}{__rualdi_prompt}# =============================================================================
 #
 # Convenient aliases for rualdi.
 #
@@ -140,6 +210,9 @@ unset OLDIFS
 "#,
         __rualdi_pwd = __rualdi_pwd,
         __rualdi_cd = __rualdi_cd,
+        env_naming = options.env_naming()?,
+        __rualdi_env_hook = __rualdi_env_hook,
+        __rualdi_prompt = __rualdi_prompt,
         aliases = aliases,
     )?;
 
@@ -1,12 +1,16 @@
 mod bash;
 mod zsh;
 
+use crate::config;
 #[cfg(test)]
 use crate::fixture;
+use crate::subcommand::env::{self, quote};
 use crate::subcommand::RadSubCmdRunnable;
+use crate::utils;
 use anyhow::{Context, Result};
 #[cfg(test)]
 use gag::Gag;
+use rualdlib::{Aliases, EnvCase};
 #[cfg(test)]
 use serial_test::serial;
 use structopt::clap::arg_enum;
@@ -27,6 +31,23 @@ pub struct Init {
     /// Renames the 'rad' command and corresponding aliases
     #[structopt(long, alias = "rad-cmd", default_value = "rad")]
     cmd: String,
+
+    /// Keeps the current directory abbreviated with aliases in `$rualdi_prompt`
+    #[structopt(long)]
+    prompt: bool,
+
+    /// Prefix of the alias environment variables, `_RAD_ENV_PREFIX` or `RAD_` by default
+    #[structopt(long, parse(try_from_str = config::parse_env_prefix))]
+    env_prefix: Option<String>,
+
+    /// Case of the alias environment variable names, `_RAD_ENV_CASE` or upper by default
+    #[structopt(long, possible_values = &["upper", "lower", "keep"])]
+    env_case: Option<EnvCase>,
+
+    /// Reconciles alias environment variables with the configuration file before each prompt
+    /// following a change of the file
+    #[structopt(long)]
+    env_hook: bool,
 }
 
 impl RadSubCmdRunnable for Init {
@@ -43,6 +64,32 @@ impl RadSubCmdRunnable for Init {
     }
 }
 
+impl Init {
+    /// Get the statement exporting how alias environment variables are named,
+    /// so that rualdi and the shell functions agree on the names
+    fn env_naming(&self) -> Result<String> {
+        let prefix = match &self.env_prefix {
+            Some(prefix) => prefix.to_owned(),
+            None => config::rad_env_prefix()?,
+        };
+        let case = match self.env_case {
+            Some(case) => case,
+            None => config::rad_env_case()?,
+        };
+        Ok(format!(
+            "export _RAD_ENV_PREFIX='{}' _RAD_ENV_CASE='{}'",
+            prefix, case
+        ))
+    }
+
+    /// Get the path of the configuration file watched by the env hook,
+    /// quoted for shell
+    fn aliases_file(&self, shell: &env::Shell) -> Result<String> {
+        let path = Aliases::get_path(config::rad_aliases_dir()?);
+        Ok(quote(&utils::path_bytes(path), shell))
+    }
+}
+
 arg_enum! {
     #[allow(non_camel_case_types)]
     #[derive(Debug)]
@@ -62,6 +109,10 @@ mod tests {
         let subcmd = fixture::create_subcmd(Init {
             shell: Shell::zsh,
             cmd: String::from("rad"),
+            prompt: true,
+            env_prefix: Some(String::from("PROJ_")),
+            env_case: Some(EnvCase::Keep),
+            env_hook: true,
         });
         let _print_gag = Gag::stdout().unwrap();
         let res = subcmd.run();
@@ -74,6 +125,10 @@ mod tests {
         let subcmd = fixture::create_subcmd(Init {
             shell: Shell::bash,
             cmd: String::from("rad"),
+            prompt: true,
+            env_prefix: None,
+            env_case: None,
+            env_hook: true,
         });
         let _print_gag = Gag::stdout().unwrap();
         let res = subcmd.run();
@@ -1,5 +1,6 @@
 use super::Init;
 use crate::config;
+use crate::subcommand::env::Shell;
 
 use anyhow::Result;
 use std::io::Write;
@@ -38,6 +39,49 @@ function __rualdi_cd() {
 }"#
     };
 
+    let __rualdi_prompt = if options.prompt {
+        r#"# =============================================================================
+# Prompt segment, `$rualdi_prompt` holds the current directory abbreviated
+# with aliases and %HASH mappings, use it with `setopt prompt_subst`
+typeset -g rualdi_prompt
+function __rualdi_prompt_precmd() {
+    rualdi_prompt="$(rualdi abbrev -- "$PWD" 2>/dev/null)" || rualdi_prompt=${(D)PWD}
+    # Keep %HASH mappings from being read as prompt escapes
+    rualdi_prompt=${rualdi_prompt//\%/%%}
+}
+autoload -Uz add-zsh-hook
+add-zsh-hook precmd __rualdi_prompt_precmd
+"#
+    } else {
+        ""
+    };
+
+    let __rualdi_env_hook = if options.env_hook {
+        format!(
+            r#"# Reconcile environment variables with the configuration file before each
+# prompt following a change of the file, variables changed or removed from
+# another shell are updated. The modification time and size of the file
+# are recorded in `__rualdi_env_stamp`
+zmodload -F zsh/stat b:zstat
+typeset -g __rualdi_env_stamp
+function __rualdi_env_precmd() {{
+    local -A __rualdi_stat
+    local __rualdi_stamp
+    zstat -H __rualdi_stat -- {file} 2>/dev/null &&
+        __rualdi_stamp="${{__rualdi_stat[mtime]}} ${{__rualdi_stat[size]}}"
+    [[ "$__rualdi_stamp" == "$__rualdi_env_stamp" ]] && return
+    __rualdi_env_stamp=$__rualdi_stamp
+    eval "$(_RAD_EXPORTED_VARS=$_RAD_EXPORTED_VARS rualdi env --shell zsh --track 2>/dev/null)"
+}}
+autoload -Uz add-zsh-hook
+add-zsh-hook precmd __rualdi_env_precmd
+"#,
+            file = options.aliases_file(&Shell::zsh)?
+        )
+    } else {
+        String::new()
+    };
+
     let aliases = format!(
         r#"
 alias {cmd}='__rualdi_rad'
@@ -48,6 +92,7 @@ alias {cmd}a='__rualdi_rada'
 alias {cmd}ax='__rualdi_radax'
 alias {cmd}l='__rualdi_radl'
 alias {cmd}r='__rualdi_radr'
+alias {cmd}mv='__rualdi_radmv'
 alias {cmd}rx='__rualdi_radrx'"#,
         cmd = options.cmd
     );
@@ -62,6 +107,19 @@ alias {cmd}rx='__rualdi_radrx'"#,
 {__rualdi_pwd}
 # cd + custom logic + resolving based on the value of `_RAD_NO_ECHO`
 {__rualdi_cd}
+
+# Naming of alias environment variables, from `--env-prefix` and `--env-case`
+# or the values of `_RAD_ENV_PREFIX` and `_RAD_ENV_CASE`
+{env_naming}
+
+# Get the name the environment variable of an alias is exported as
+function __rualdi_env_name() {{
+    case "$_RAD_ENV_CASE" in
+        upper) builtin print -r -- "$_RAD_ENV_PREFIX${{1:u}}" ;;
+        lower) builtin print -r -- "$_RAD_ENV_PREFIX${{1:l}}" ;;
+        *) builtin print -r -- "$_RAD_ENV_PREFIX$1" ;;
+    esac
+}}
 # =============================================================================
 
 # Error wrapper
@@ -106,12 +164,22 @@ function __rualdi_fzf_list() {{
 
 # Combine above fzf functions into one. Same as __rualdi_cd; however, fzf is involved
 # Has an option to switch to recent directories as well using '-d'
+# and to only pick aliases carrying a tag using '-t <tag>' (can be repeated),
+# `--mkdir`, `--no-check` and `--ancestor` are forwarded to `rualdi resolve`
 function __rualdi_fzf {{
     setopt extendedglob noshortloops rcexpandparam
     zmodload -Fa zsh/parameter p:commands p:dirstack
 
+    local -a tags opts
+    while [[ $# -ge 2 && "$1" = (-t|--tag) ]]; do
+        tags+=( --tag "$2" )
+        shift 2
+    done
+    opts=( ${{(M)@:#--(mkdir|no-check|ancestor)}} )
+    argv=( "${{@:#--(mkdir|no-check|ancestor)}}" )
+
     typeset -gaH rualdi_aliases
-    rualdi_aliases=( ${{(@f)"$(rualdi list-alias)"}} )
+    rualdi_aliases=( ${{(@f)"$(rualdi list-alias --sort frecency $tags)"}} )
 
     if [[ $# -eq 1 && "$1" = '-' ]]; then
         if [[ -n "$OLDPWD" ]]; then
@@ -134,21 +202,22 @@ function __rualdi_fzf {{
             alias_dirs=( ${{rualdi_aliases[@]//(#m)*/${{(@)${{(@As: => :)${{MATCH}}}}[1]}}}} )
             # Note: :* checks whether argv is contained in alias_dirs
             [[ -n "${{argv:*alias_dirs}}" ]] && {{
-                __rualdi_cd "$(rualdi resolve -- "$argv")" && return
+                __rualdi_cd "$(rualdi resolve --record $opts -- "$argv")" && return
             }}
         }}
         # ]]]
 
         sel=${{${{(@s:→:)$(__rualdi_fzf_list \
             | __rualdi_colorize \
-            | __rualdi_fzf_ --query="${{argv:-}}")}}[1]}}
+            | __rualdi_fzf_ --tiebreak=index --query="${{argv:-}}")}}[1]}}
 
         [[ -n "$sel" ]] &&
-            __rualdi_cd "$(rualdi resolve -- "$sel")"
+            __rualdi_cd "$(rualdi resolve --record $opts -- "$sel")"
     fi
 }}
 
-# Jump to a directory using alias
+# Jump to a directory using alias, `--mkdir`, `--no-check` and `--ancestor`
+# are forwarded to `rualdi resolve`
 function __rualdi_rad() {{
     if [[ $# -eq 1 && "$1" = '-' ]]; then
         if [[ -n "$OLDPWD" ]]; then
@@ -161,7 +230,10 @@ function __rualdi_rad() {{
         [[ ! -o pushdminus ]] && __rualdi_cd "${{1/-/+}}" || __rualdi_cd "$1"
     else
         local __rualdi_result
-        __rualdi_result="$(rualdi resolve -- "$@")" &&
+        local -a __rualdi_opts
+        # Forward resolve flags, everything else is the path to resolve
+        __rualdi_opts=( ${{(M)@:#--(mkdir|no-check|ancestor)}} )
+        __rualdi_result="$(rualdi resolve --record $__rualdi_opts -- "${{@:#--(mkdir|no-check|ancestor)}}")" &&
             __rualdi_cd "$__rualdi_result"
     fi
 }}
@@ -174,39 +246,58 @@ function __rualdi_rada() {{
 # Add a new alias environment variable to the current environment
 # and fill rualdi aliases configuration file
 function __rualdi_radx() {{
-    local __rualdi_alias=$1 __rualdi_var
     rualdi add-env -- "$@" && \
-    __rualdi_var="$(rualdi resolve-env -- $__rualdi_alias)" && \
-    export RAD_$__rualdi_var="$(rualdi resolve -- "$__rualdi_alias")" && \
-    echo "Environment variable 'RAD_$__rualdi_var' added to current environment"
+    eval "$(rualdi env --shell zsh --track)"
 }}
 
 # Add a new alias environment variable to the current environment
 # without filling rualdi aliases configuration file
 function __rualdi_radxn() {{
     local __rualdi_alias=$1 __rualdi_var=$2
-    test "$__rualdi_var" && __rualdi_var=${{__rualdi_var:u}} || __rualdi_var=${{__rualdi_alias:u}}
-    export RAD_$__rualdi_var="$(rualdi resolve -- "$__rualdi_alias")" && \
-    echo "Environment variable 'RAD_$__rualdi_var' added to current environment without filling rualdi alias configuration file"
+    __rualdi_var="$(__rualdi_env_name "${{__rualdi_var:-${{__rualdi_alias//\//_}}}}")"
+    export $__rualdi_var="$(rualdi resolve -- "$__rualdi_alias")" && \
+    echo "Environment variable '$__rualdi_var' added to current environment without filling rualdi alias configuration file"
 }}
 
 # Add a new alias to the rualdi aliases configuration file with environment variable associated
 function __rualdi_radax() {{
-    local __rualdi_alias=$1 __rualdi_path=$2 __rualdi_var=$3 __rualdi_get
+    local __rualdi_alias=$1 __rualdi_path=$2 __rualdi_var=$3
     rualdi add -- $__rualdi_alias $__rualdi_path && \
     rualdi add-env -- $__rualdi_alias $__rualdi_var && \
-    __rualdi_get="$(rualdi resolve-env -- $__rualdi_alias)" && \
-    export RAD_$__rualdi_get=$(rualdi resolve -- $__rualdi_alias)
+    eval "$(rualdi env --shell zsh --track)"
 }}
 
 # Remove an alias to the rualdi aliases configuration file
+# and unset its environment variables
 function __rualdi_radr() {{
-    rualdi remove -- "$@"
+    local __rualdi_vars __rualdi_ret
+    __rualdi_vars="$(rualdi remove --porcelain -- "$@")"
+    __rualdi_ret=$?
+    [[ -n "$__rualdi_vars" ]] && unset ${{(f)__rualdi_vars}}
+    return $__rualdi_ret
 }}
 
-# Remove an alias environment variable to the rualdi aliases configuration file.
+# Rename an alias in the rualdi aliases configuration file
+# and update its environment variables in the current environment
+function __rualdi_radmv() {{
+    local __rualdi_old_vars
+    __rualdi_old_vars="$(rualdi resolve-env --exported -- "$1" 2>/dev/null)"
+    rualdi rename -- "$@" || return
+    [[ -n "$__rualdi_old_vars" ]] && {{
+        unset ${{(f)__rualdi_old_vars}}
+        eval "$(rualdi env --shell zsh --track)"
+    }}
+    return 0
+}}
+
+# Remove alias environment variables to the rualdi aliases configuration file
+# and unset them
 function __rualdi_radrx() {{
-    rualdi remove-env -- "$@"
+    local __rualdi_vars __rualdi_ret
+    __rualdi_vars="$(rualdi remove-env --porcelain -- "$@")"
+    __rualdi_ret=$?
+    [[ -n "$__rualdi_vars" ]] && unset ${{(f)__rualdi_vars}}
+    return $__rualdi_ret
 }}
 
 # List aliases and alias environment variables to the rualdi aliases configuration file.
@@ -216,18 +307,8 @@ function __rualdi_radl() {{
 
 # =============================================================================
 # Restore environment variables
-OLDIFS="$IFS"
-IFS=$'\n'
-for line in $(rualdi list-env); do
-    local __rualdi_alias __rualdi_path __rualdi__var
-    __rualdi_alias=$(echo $line | cut -d' ' -f1)
-    __rualdi_var=$(echo $line | cut -d' ' -f2)
-    __rualdi_path=$(rualdi resolve -- $__rualdi_alias)
-    export RAD_${{__rualdi_var:u}}="$__rualdi_path"
-done
-IFS="$OLDIFS"
-unset OLDIFS
-# =============================================================================
+eval "$(rualdi env --shell zsh --track)"
+{__rualdi_env_hook}{__rualdi_prompt}# =============================================================================
 # Convenient aliases for rualdi
 {aliases}
 # =============================================================================
@@ -238,6 +319,9 @@ unset OLDIFS
 "###,
         __rualdi_pwd = __rualdi_pwd,
         __rualdi_cd = __rualdi_cd,
+        env_naming = options.env_naming()?,
+        __rualdi_env_hook = __rualdi_env_hook,
+        __rualdi_prompt = __rualdi_prompt,
         aliases = aliases,
     )?;
 
@@ -3,7 +3,7 @@ use crate::config;
 use crate::fixture;
 use crate::subcommand::RadSubCmdRunnable;
 use anyhow::{Context, Result};
-use rualdlib::Aliases;
+use rualdlib::{Aliases, Sort};
 #[cfg(test)]
 use serial_test::serial;
 use structopt::StructOpt;
@@ -12,16 +12,30 @@ use terminal_size::terminal_size;
 
 /// Print aliases with their path and environment variable associated
 #[derive(Debug, StructOpt)]
-pub struct List {}
+pub struct List {
+    /// Only list aliases carrying this tag, can be repeated
+    #[structopt(short, long = "tag", number_of_values = 1)]
+    pub tags: Vec<String>,
+    /// Print a section per tag
+    #[structopt(short, long)]
+    pub group: bool,
+    /// Order of listed aliases
+    #[structopt(short, long, default_value = "name", possible_values = Sort::VARIANTS)]
+    pub sort: Sort,
+}
 
 impl RadSubCmdRunnable for List {
     fn run(&self) -> Result<String> {
         let aliases_dir = config::rad_aliases_dir().with_context(|| "fail to list aliases")?;
-        let aliases = Aliases::open(aliases_dir).with_context(|| "fail to list aliases")?;
+        let mut aliases = Aliases::open(aliases_dir).with_context(|| "fail to list aliases")?;
+        config::set_env_naming(&mut aliases)?;
 
-        let res = aliases
-            .list()
-            .unwrap_or_else(|| "No aliases found\n".into());
+        let res = if self.group {
+            aliases.list_grouped(&self.tags, self.sort)
+        } else {
+            aliases.list(&self.tags, self.sort)
+        }
+        .unwrap_or_else(|| "No aliases found\n".into());
 
         Ok(res)
     }
@@ -34,7 +48,11 @@ mod tests {
     #[test]
     #[serial]
     fn no_aliases() {
-        let subcmd = fixture::create_subcmd(List {});
+        let subcmd = fixture::create_subcmd(List {
+            tags: Vec::new(),
+            group: false,
+            sort: Sort::Name,
+        });
         let res = subcmd.run();
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), "No aliases found\n");
@@ -43,7 +61,11 @@ mod tests {
     #[test]
     #[serial]
     fn alias() {
-        let mut subcmd = fixture::create_subcmd(List {});
+        let mut subcmd = fixture::create_subcmd(List {
+            tags: Vec::new(),
+            group: false,
+            sort: Sort::Name,
+        });
         subcmd.use_config(toml::toml![
             [aliases]
             test = "test"
@@ -67,7 +89,11 @@ mod tests {
     #[test]
     #[serial]
     fn aliases() {
-        let mut subcmd = fixture::create_subcmd(List {});
+        let mut subcmd = fixture::create_subcmd(List {
+            tags: Vec::new(),
+            group: false,
+            sort: Sort::Name,
+        });
         subcmd.use_config(toml::toml![
             [aliases]
             test = "test"
@@ -92,7 +118,11 @@ mod tests {
     #[test]
     #[serial]
     fn vars() {
-        let mut subcmd = fixture::create_subcmd(List {});
+        let mut subcmd = fixture::create_subcmd(List {
+            tags: Vec::new(),
+            group: false,
+            sort: Sort::Name,
+        });
         subcmd.use_config(toml::toml![
             [aliases]
             test = "test"
@@ -118,4 +148,36 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    #[serial]
+    fn grouped() {
+        let mut subcmd = fixture::create_subcmd(List {
+            tags: Vec::new(),
+            group: true,
+            sort: Sort::Name,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            test = "test"
+            api = { path = "api", tags = ["work"] }
+        ]);
+        let res = subcmd.run();
+        assert!(res.is_ok());
+        let width = terminal_size().map(|(w, _)| w.0 as usize).unwrap_or(1);
+        let equal_line = "=".repeat(width);
+        assert_eq!(
+            res.unwrap(),
+            format!(
+                "{}\n{: ^width$}\n{}\napi          => api\n{}\n{: ^width$}\n{}\ntest         => test\n",
+                equal_line,
+                "TAG: work",
+                equal_line,
+                equal_line,
+                "UNTAGGED",
+                equal_line,
+                width = width - 1
+            )
+        );
+    }
 }
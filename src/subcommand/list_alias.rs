@@ -3,14 +3,21 @@ use crate::config;
 use crate::fixture;
 use crate::subcommand::RadSubCmdRunnable;
 use anyhow::{Context, Result};
-use rualdlib::Aliases;
+use rualdlib::{Aliases, Sort};
 #[cfg(test)]
 use serial_test::serial;
 use structopt::StructOpt;
 
 /// Print alias and its associated path (not colored)
 #[derive(Debug, StructOpt)]
-pub struct ListAlias {}
+pub struct ListAlias {
+    /// Only list aliases carrying this tag, can be repeated
+    #[structopt(short, long = "tag", number_of_values = 1)]
+    pub tags: Vec<String>,
+    /// Order of listed aliases
+    #[structopt(short, long, default_value = "name", possible_values = Sort::VARIANTS)]
+    pub sort: Sort,
+}
 
 impl RadSubCmdRunnable for ListAlias {
     fn run(&self) -> Result<String> {
@@ -18,7 +25,7 @@ impl RadSubCmdRunnable for ListAlias {
         let aliases = Aliases::open(aliases_dir).with_context(|| "fail to list aliases")?;
 
         let res = aliases
-            .list_alias()
+            .list_alias(&self.tags, self.sort)
             .unwrap_or_else(|| "No aliases found\n".into());
 
         Ok(res)
@@ -32,7 +39,10 @@ mod tests {
     #[test]
     #[serial]
     fn no_aliases() {
-        let subcmd = fixture::create_subcmd(ListAlias {});
+        let subcmd = fixture::create_subcmd(ListAlias {
+            tags: Vec::new(),
+            sort: Sort::Name,
+        });
         let res = subcmd.run();
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), "No aliases found\n");
@@ -41,7 +51,10 @@ mod tests {
     #[test]
     #[serial]
     fn alias() {
-        let mut subcmd = fixture::create_subcmd(ListAlias {});
+        let mut subcmd = fixture::create_subcmd(ListAlias {
+            tags: Vec::new(),
+            sort: Sort::Name,
+        });
         subcmd.use_config(toml::toml![
             [aliases]
             test = "test"
@@ -54,7 +67,10 @@ mod tests {
     #[test]
     #[serial]
     fn aliases() {
-        let mut subcmd = fixture::create_subcmd(ListAlias {});
+        let mut subcmd = fixture::create_subcmd(ListAlias {
+            tags: Vec::new(),
+            sort: Sort::Name,
+        });
         subcmd.use_config(toml::toml![
             [aliases]
             test = "test"
@@ -68,7 +84,10 @@ mod tests {
     #[test]
     #[serial]
     fn vars() {
-        let mut subcmd = fixture::create_subcmd(ListAlias {});
+        let mut subcmd = fixture::create_subcmd(ListAlias {
+            tags: Vec::new(),
+            sort: Sort::Name,
+        });
         subcmd.use_config(toml::toml![
             [aliases]
             test = "test"
@@ -80,4 +99,21 @@ mod tests {
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), "test => test\ntest2 => test2\n");
     }
+
+    #[test]
+    #[serial]
+    fn tagged() {
+        let mut subcmd = fixture::create_subcmd(ListAlias {
+            tags: vec![String::from("work")],
+            sort: Sort::Name,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            test = "test"
+            api = { path = "api", tags = ["work"] }
+        ]);
+        let res = subcmd.run();
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), "api => api\n");
+    }
 }
@@ -1,26 +1,40 @@
+mod abbrev;
 mod add;
 mod add_env;
 mod completions;
+mod env;
+mod export_env;
 mod init;
 mod list;
 mod list_alias;
 mod list_env;
 mod remove;
 mod remove_env;
+mod rename;
 mod resolve;
 mod resolve_env;
+mod set;
+mod stats;
+mod which;
 
+pub use abbrev::Abbrev;
 pub use add::Add;
 pub use add_env::AddEnv;
 pub use completions::Completions;
+pub use env::Env;
+pub use export_env::ExportEnv;
 pub use init::Init;
 pub use list::List;
 pub use list_alias::ListAlias;
 pub use list_env::ListEnv;
 pub use remove::Remove;
 pub use remove_env::RemoveEnv;
+pub use rename::Rename;
 pub use resolve::Resolve;
 pub use resolve_env::ResolveEnv;
+pub use set::Set;
+pub use stats::Stats;
+pub use which::Which;
 
 use anyhow::Result;
 
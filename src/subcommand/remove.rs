@@ -2,6 +2,7 @@ use crate::config;
 use crate::ctype_exp;
 #[cfg(test)]
 use crate::fixture;
+use crate::subcommand::env::checked_env_name;
 use crate::subcommand::RadSubCmdRunnable;
 use anyhow::{Context, Result};
 use colored::*;
@@ -10,36 +11,71 @@ use rualdlib::Aliases;
 use serial_test::serial;
 use structopt::StructOpt;
 
-/// Remove alias
+/// Remove alias and its environment variables
 #[derive(Debug, StructOpt)]
 pub struct Remove {
     /// Alias to remove
     pub alias: Vec<String>,
+    /// Print the names removed environment variables were exported as, one
+    /// per line, other messages are printed on standard error
+    #[structopt(long)]
+    pub porcelain: bool,
 }
 
 impl RadSubCmdRunnable for Remove {
     fn run(&self) -> Result<String> {
         let aliases_dir = config::rad_aliases_dir().with_context(|| "fail to remove alias")?;
         let mut aliases = Aliases::open(aliases_dir).with_context(|| "fail to remove alias")?;
+        config::set_env_naming(&mut aliases)?;
+        // Names are printed as soon as variables are removed, so that they
+        // are reported even if a later alias cannot be removed
+        let report = |message: String| {
+            if self.porcelain {
+                eprintln!("{}", message);
+            } else {
+                println!("{}", message);
+            }
+        };
 
         for alias in &self.alias {
-            aliases.remove(alias.to_owned()).with_context(|| {
+            let base = aliases.remove(alias.to_owned()).with_context(|| {
                 format!(
                     "[{}] Failed to remove: {}",
                     ctype_exp!("alias"),
                     alias.red().bold()
                 )
             })?;
-            println!("[{}] Removed: {}", ctype_exp!("alias"), alias.red().bold());
-            if let Ok(var) = aliases.get_env(alias) {
-                aliases.remove_env(alias.to_owned())?;
-                println!(
+            report(format!(
+                "[{}] Removed: {}",
+                ctype_exp!("alias"),
+                alias.red().bold()
+            ));
+            // The alias still exists, its variables are kept
+            if let Some(base) = base {
+                report(format!(
+                    "[{}] Base alias active again: {} => {}",
+                    ctype_exp!("alias"),
+                    alias.green().bold(),
+                    base.path_lossy()
+                ));
+                continue;
+            }
+            for (var, entry) in aliases
+                .remove_env(alias.to_owned(), &[])
+                .unwrap_or_default()
+            {
+                if self.porcelain {
+                    if let Some(name) = checked_env_name(&aliases, &var, entry.prefixed) {
+                        println!("{}", name);
+                    }
+                }
+                report(format!(
                     "[{}] Removed: {} for [{}] {}",
                     ctype_exp!("env"),
                     var.red().bold(),
                     ctype_exp!("alias"),
                     alias.red().bold()
-                );
+                ));
             }
         }
 
@@ -56,6 +92,7 @@ mod tests {
     fn not_existing_alias() {
         let subcmd = fixture::create_subcmd(Remove {
             alias: vec![String::from("test")],
+            porcelain: false,
         });
         let res = subcmd.run();
         assert!(res.is_err());
@@ -70,6 +107,7 @@ mod tests {
     fn existing_alias() {
         let mut subcmd = fixture::create_subcmd(Remove {
             alias: vec![String::from("test")],
+            porcelain: false,
         });
         subcmd.use_config(toml::toml![
             [aliases]
@@ -85,6 +123,7 @@ mod tests {
     fn existing_aliases() {
         let mut subcmd = fixture::create_subcmd(Remove {
             alias: vec![String::from("test"), String::from("test2")],
+            porcelain: false,
         });
         subcmd.use_config(toml::toml![
             [aliases]
@@ -2,6 +2,7 @@ use crate::config;
 use crate::ctype_exp;
 #[cfg(test)]
 use crate::fixture;
+use crate::subcommand::env::checked_env_name;
 use crate::subcommand::RadSubCmdRunnable;
 use anyhow::{Context, Result};
 use colored::*;
@@ -10,11 +11,17 @@ use rualdlib::Aliases;
 use serial_test::serial;
 use structopt::StructOpt;
 
-/// Remove environment variable for a provided alias
+/// Remove environment variables for a provided alias
 #[derive(Debug, StructOpt)]
 pub struct RemoveEnv {
-    /// Alias for which to remove the environment variable
+    /// Alias for which to remove the environment variables
     pub alias: String,
+    /// Environment variables to remove, all variables of alias if not provided
+    pub vars: Vec<String>,
+    /// Print the names removed environment variables were exported as, one
+    /// per line, other messages are printed on standard error
+    #[structopt(long)]
+    pub porcelain: bool,
 }
 
 impl RadSubCmdRunnable for RemoveEnv {
@@ -37,23 +44,39 @@ impl RadSubCmdRunnable for RemoveEnv {
                 self.alias.red().bold()
             )
         })?;
+        config::set_env_naming(&mut aliases)?;
 
-        aliases.remove_env(self.alias.to_owned()).with_context(|| {
-            format!(
-                "[{}] Failed to remove for [{}] {}",
-                ctype_exp!("env"),
-                ctype_exp!("alias"),
-                self.alias.red().bold()
-            )
-        })?;
+        let removed = aliases
+            .remove_env(self.alias.to_owned(), &self.vars)
+            .with_context(|| {
+                format!(
+                    "[{}] Failed to remove for [{}] {}",
+                    ctype_exp!("env"),
+                    ctype_exp!("alias"),
+                    self.alias.red().bold()
+                )
+            })?;
 
         // "environment variable for alias '{}' removed\n",
-        Ok(format!(
+        let message = format!(
             "[{}] Removed for [{}] {}",
             ctype_exp!("env"),
             ctype_exp!("alias"),
             self.alias.red().bold()
-        ))
+        );
+        if !self.porcelain {
+            return Ok(message);
+        }
+
+        eprintln!("{}", message);
+        let mut res = String::new();
+        for (var, entry) in removed {
+            if let Some(name) = checked_env_name(&aliases, &var, entry.prefixed) {
+                res.push_str(&name);
+                res.push('\n');
+            }
+        }
+        Ok(res)
     }
 }
 
@@ -66,6 +89,8 @@ mod tests {
     fn existing_var() {
         let mut subcmd = fixture::create_subcmd(RemoveEnv {
             alias: String::from("test"),
+            vars: Vec::new(),
+            porcelain: false,
         });
         subcmd.use_config(toml::toml!(
             [aliases]
@@ -86,11 +111,74 @@ mod tests {
         );
     }
 
+    #[test]
+    #[serial]
+    fn some_vars() {
+        let mut subcmd = fixture::create_subcmd(RemoveEnv {
+            alias: String::from("test"),
+            vars: vec![String::from("TEST_SRC")],
+            porcelain: false,
+        });
+        subcmd.use_config(toml::toml!(
+            version = 6
+            [aliases]
+            test = "test"
+            [environment]
+            TEST = "test"
+            TEST_SRC = "test/src"
+        ));
+        assert!(subcmd.run().is_ok());
+        let aliases = Aliases::open(subcmd.tmp.tmp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(aliases.get_env("test").unwrap(), vec![String::from("TEST")]);
+    }
+
+    #[test]
+    #[serial]
+    fn porcelain() {
+        let mut subcmd = fixture::create_subcmd(RemoveEnv {
+            alias: String::from("test"),
+            vars: Vec::new(),
+            porcelain: true,
+        });
+        subcmd.use_config(toml::toml!(
+            version = 6
+            [aliases]
+            test = "test"
+            [environment]
+            TEST = "test"
+            TEST_ROOT = { alias = "test", prefix = false }
+        ));
+        assert_eq!(subcmd.run().unwrap(), "RAD_TEST\nTEST_ROOT\n");
+    }
+
+    #[test]
+    #[serial]
+    fn porcelain_invalid_names() {
+        let mut subcmd = fixture::create_subcmd(RemoveEnv {
+            alias: String::from("test"),
+            vars: Vec::new(),
+            porcelain: true,
+        });
+        subcmd.use_config(toml::toml!(
+            version = 6
+            [aliases]
+            test = "test"
+            [environment]
+            TEST = "test"
+            PATH = { alias = "test", prefix = false }
+            "my-var" = { alias = "test", prefix = false }
+            "*" = { alias = "test", prefix = false }
+        ));
+        assert_eq!(subcmd.run().unwrap(), "RAD_TEST\n");
+    }
+
     #[test]
     #[serial]
     fn not_existing_alias() {
         let subcmd = fixture::create_subcmd(RemoveEnv {
             alias: String::from("test"),
+            vars: Vec::new(),
+            porcelain: false,
         });
         let res = subcmd.run();
         assert!(res.is_err());
@@ -110,6 +198,8 @@ mod tests {
     fn not_existing_var() {
         let mut subcmd = fixture::create_subcmd(RemoveEnv {
             alias: String::from("test"),
+            vars: Vec::new(),
+            porcelain: false,
         });
         subcmd.use_config(toml::toml!(
             [aliases]
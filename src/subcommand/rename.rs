@@ -0,0 +1,138 @@
+use crate::config;
+#[cfg(test)]
+use crate::fixture;
+use crate::subcommand::RadSubCmdRunnable;
+use anyhow::{Context, Result};
+use colored::*;
+use rualdlib::Aliases;
+#[cfg(test)]
+use serial_test::serial;
+use structopt::StructOpt;
+
+/// Rename alias, its environment variables are kept
+#[derive(Debug, StructOpt)]
+pub struct Rename {
+    /// Alias to rename
+    pub alias: String,
+    /// New name of the alias
+    pub new_alias: String,
+    /// New name of the environment variable linked to the alias, only when
+    /// the alias has a single one
+    pub var: Option<String>,
+}
+
+impl RadSubCmdRunnable for Rename {
+    fn run(&self) -> Result<String> {
+        let context = || {
+            format!(
+                "[{}] Failed to rename: {}",
+                ctype_exp!("alias"),
+                self.alias.red().bold()
+            )
+        };
+        let aliases_dir = config::rad_aliases_dir().with_context(context)?;
+        let mut aliases = Aliases::open(aliases_dir).with_context(context)?;
+
+        config::set_env_naming(&mut aliases).with_context(context)?;
+        let old_vars = aliases.get_env(&self.alias).unwrap_or_default();
+        let case = config::rad_env_case().with_context(context)?;
+        let var = self.var.as_ref().map(|var| case.apply(var));
+        aliases
+            .rename(self.alias.to_owned(), self.new_alias.to_owned(), var)
+            .with_context(context)?;
+
+        let mut res = format!(
+            "[{}] Renamed: {} to {}\n",
+            ctype_exp!("alias"),
+            self.alias.red().bold(),
+            self.new_alias.green().bold()
+        );
+        // Variables keep their order unless the single one is renamed
+        let vars = aliases.get_env(&self.new_alias).unwrap_or_default();
+        for (old_var, var) in old_vars.iter().zip(vars.iter()) {
+            if old_var == var {
+                res.push_str(&format!(
+                    "[{}] Moved: {} to [{}] {}\n",
+                    ctype_exp!("env"),
+                    var.green().bold(),
+                    ctype_exp!("alias"),
+                    self.new_alias.green().bold()
+                ));
+            } else {
+                res.push_str(&format!(
+                    "[{}] Renamed: {} to {} for [{}] {}\n",
+                    ctype_exp!("env"),
+                    old_var.red().bold(),
+                    var.green().bold(),
+                    ctype_exp!("alias"),
+                    self.new_alias.green().bold()
+                ));
+            }
+        }
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[serial]
+    fn not_existing_alias() {
+        let subcmd = fixture::create_subcmd(Rename {
+            alias: String::from("test"),
+            new_alias: String::from("renamed"),
+            var: None,
+        });
+        let res = subcmd.run();
+        assert!(res.is_err());
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "[alias] Failed to rename: test"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn existing_alias() {
+        let mut subcmd = fixture::create_subcmd(Rename {
+            alias: String::from("test"),
+            new_alias: String::from("renamed"),
+            var: None,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            test = "test"
+        ]);
+        let res = subcmd.run();
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), "[alias] Renamed: test to renamed\n");
+    }
+
+    #[test]
+    #[serial]
+    fn with_env() {
+        let mut subcmd = fixture::create_subcmd(Rename {
+            alias: String::from("test"),
+            new_alias: String::from("renamed"),
+            var: Some(String::from("renamed")),
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            test = "test"
+            [environment]
+            test = "TEST"
+        ]);
+        let res = subcmd.run();
+        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap(),
+            "[alias] Renamed: test to renamed\n[env] Renamed: TEST to RENAMED for [alias] renamed\n"
+        );
+
+        let aliases = Aliases::open(subcmd.tmp.tmp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(aliases.get_env("renamed").unwrap(), vec!["RENAMED"]);
+    }
+}
@@ -3,9 +3,9 @@ use crate::config;
 use crate::fixture;
 use crate::subcommand::RadSubCmdRunnable;
 use crate::utils;
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use colored::*;
-use rualdlib::Aliases;
+use rualdlib::{Aliases, Matching};
 #[cfg(test)]
 use serial_test::serial;
 use std::ffi::OsStr;
@@ -18,11 +18,32 @@ use structopt::StructOpt;
 #[derive(Debug, StructOpt)]
 pub struct Resolve {
     /// Path to resolve alias
+    #[structopt(parse(from_os_str))]
     pub path: PathBuf,
+    /// Record the jump to the alias to rank aliases by frecency
+    #[structopt(long)]
+    pub record: bool,
+    /// Create the missing directories of the resolved path,
+    /// which must be below the path of an alias
+    #[structopt(long, conflicts_with_all = &["no-check", "ancestor"])]
+    pub mkdir: bool,
+    /// Print the resolved path without checking it exists
+    #[structopt(long, conflicts_with = "ancestor")]
+    pub no_check: bool,
+    /// Fall back to the deepest existing ancestor of a missing path
+    #[structopt(long)]
+    pub ancestor: bool,
 }
 
 impl RadSubCmdRunnable for Resolve {
     fn run(&self) -> Result<String> {
+        Ok(format!("{}\n", self.resolve()?.display()))
+    }
+}
+
+impl Resolve {
+    /// Get the resolved path, as is when it is not valid UTF-8
+    pub fn resolve(&self) -> Result<PathBuf> {
         let aliases_dir = config::rad_aliases_dir().with_context(|| {
             format!(
                 "failed to resolve alias path '{}'",
@@ -36,38 +57,85 @@ impl RadSubCmdRunnable for Resolve {
             )
         })?;
 
-        let path;
+        let context = || {
+            format!(
+                "failed to resolve alias for path '{}'",
+                self.path.display().to_string().green().bold()
+            )
+        };
+        let (resolved_path, alias) = if self.path.is_absolute() {
+            (self.path.to_owned(), None)
+        } else {
+            let matching = config::rad_match()?;
+            resolve_alias(&self.path, &aliases, matching).with_context(context)?
+        };
+        if self.mkdir {
+            check_below_alias(&resolved_path, alias.as_deref(), &aliases).with_context(context)?;
+        }
+        let path = self.check_path(&resolved_path)?;
+
+        if let Some(alias) = alias.filter(|_| self.record) {
+            record_visit(&alias, &aliases);
+        }
+        Ok(path)
+    }
 
-        if self.path.is_absolute() {
-            path = utils::resolve_path(&self.path)?;
+    /// Make path absolute and check it according to the resolve mode
+    fn check_path(&self, path: &Path) -> Result<PathBuf> {
+        let mode = config::rad_path_mode();
+        if self.mkdir {
+            utils::create_path(path, mode)
+        } else if self.no_check {
+            utils::normalize_path(path, mode)
+        } else if self.ancestor {
+            let ancestor = utils::resolve_ancestor(path, mode)?;
+            let wanted = utils::normalize_path(path, mode)?;
+            if ancestor != wanted {
+                eprintln!(
+                    "[{}] Path {} does not exist, falling back to {}",
+                    ctype_exp!("alias"),
+                    wanted.display().to_string().red().bold(),
+                    ancestor.display().to_string().green().bold()
+                );
+            }
+            Ok(ancestor)
         } else {
-            let resolved_path = resolve_alias(&self.path, aliases).with_context(|| {
-                format!(
-                    "failed to resolve alias for path '{}'",
-                    self.path.display().to_string().green().bold()
-                )
-            })?;
-            path = utils::resolve_path(&resolved_path)?;
+            utils::resolve_path(path, mode)
         }
-        Ok(format!("{}\n", path.display()))
     }
 }
 
-/// Get path radical and search it in config file,
-/// return original path if no alias found
-fn resolve_alias<P: AsRef<Path>>(path: P, aliases: Aliases) -> Result<PathBuf> {
+/// Get path radical and search it in config file, return original
+/// path if no alias found along with the alias matched
+fn resolve_alias<P: AsRef<Path>>(
+    path: P,
+    aliases: &Aliases,
+    matching: Matching,
+) -> Result<(PathBuf, Option<String>)> {
     let path = path.as_ref();
 
     let mut components = path.components().peekable();
     let component = components.next().unwrap().as_os_str();
-    let to_find = component
-        .to_str()
-        .with_context(|| format!("invalid utf-8 sequence in alias part: {:?}", component))?;
+    // Aliases are valid UTF-8, other paths are not aliased
+    let to_find = match component.to_str() {
+        Some(to_find) => to_find,
+        None => return Ok((path.to_path_buf(), None)),
+    };
 
-    let alias = aliases.get(to_find);
-    let result = match alias {
+    // An existing relative directory wins over an alias partially matching it
+    let matching = if Path::new(component).is_dir() {
+        Matching::Exact
+    } else {
+        matching
+    };
+    let alias = aliases.find(to_find, matching)?;
+    let pointed = match &alias {
+        Some(alias) => aliases.get_raw(alias)?,
+        None => None,
+    };
+    let result = match pointed {
         Some(alias) => {
-            let mut resolved: Vec<&OsStr> = vec![OsStr::new(&alias)];
+            let mut resolved: Vec<&OsStr> = vec![alias.as_os_str()];
             resolved.extend(
                 components
                     .map(|comp| comp.as_os_str())
@@ -78,7 +146,41 @@ fn resolve_alias<P: AsRef<Path>>(path: P, aliases: Aliases) -> Result<PathBuf> {
         }
         None => path.to_path_buf(),
     };
-    Ok(result)
+    Ok((result, alias))
+}
+
+/// Check path lies in the directory of alias, `--mkdir` does not
+/// create directories anywhere else
+fn check_below_alias(path: &Path, alias: Option<&str>, aliases: &Aliases) -> Result<()> {
+    let alias_path = match alias {
+        Some(alias) => aliases.get_raw(alias)?,
+        None => None,
+    };
+    let mode = config::rad_path_mode();
+    match alias_path {
+        Some(alias_path)
+            if utils::normalize_path(path, mode)?
+                .starts_with(utils::normalize_path(&alias_path, mode)?) =>
+        {
+            Ok(())
+        }
+        _ => Err(anyhow!(
+            "--mkdir only creates directories below the path of an alias"
+        )),
+    }
+}
+
+/// Record a visit of alias, failing to record only prints
+/// a warning as it must not prevent the jump
+fn record_visit(alias: &str, aliases: &Aliases) {
+    if let Err(e) = aliases.record_visit(alias) {
+        eprintln!(
+            "[{}] Failed to record visit of {}: {}",
+            ctype_exp!("alias"),
+            alias.red().bold(),
+            e
+        );
+    }
 }
 
 #[cfg(test)]
@@ -91,6 +193,10 @@ mod tests {
         let current_dir = std::env::current_dir().unwrap();
         let mut subcmd = fixture::create_subcmd(Resolve {
             path: PathBuf::from_str("test").unwrap(),
+            record: false,
+            mkdir: false,
+            no_check: false,
+            ancestor: false,
         });
         subcmd.use_config(toml::toml![
             [aliases]
@@ -111,6 +217,10 @@ mod tests {
         let current_dir = std::env::current_dir().unwrap();
         let subcmd = fixture::create_subcmd(Resolve {
             path: PathBuf::from_str(current_dir.to_str().unwrap()).unwrap(),
+            record: false,
+            mkdir: false,
+            no_check: false,
+            ancestor: false,
         });
         let res = subcmd.run();
         let expected = format!("{}\n", current_dir.to_str().unwrap());
@@ -124,6 +234,10 @@ mod tests {
         let current_dir = std::env::current_dir().unwrap();
         let subcmd = fixture::create_subcmd(Resolve {
             path: PathBuf::from_str("test").unwrap(),
+            record: false,
+            mkdir: false,
+            no_check: false,
+            ancestor: false,
         });
         let res = subcmd.run();
         let expected = format!(
@@ -140,6 +254,10 @@ mod tests {
         let home_dir = std::env::var("HOME").unwrap();
         let mut subcmd = fixture::create_subcmd(Resolve {
             path: PathBuf::from_str("home").unwrap(),
+            record: false,
+            mkdir: false,
+            no_check: false,
+            ancestor: false,
         });
         subcmd.use_config(toml::toml![
             [aliases]
@@ -150,4 +268,279 @@ mod tests {
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), expected);
     }
+
+    #[test]
+    #[serial]
+    fn record() {
+        let home_dir = std::env::var("HOME").unwrap();
+        let mut subcmd = fixture::create_subcmd(Resolve {
+            path: PathBuf::from_str("home").unwrap(),
+            record: true,
+            mkdir: false,
+            no_check: false,
+            ancestor: false,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            home = "~"
+        ]);
+        let res = subcmd.run();
+        assert_eq!(res.unwrap(), format!("{}\n", home_dir));
+
+        let aliases = Aliases::open(subcmd.tmp.tmp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(
+            aliases.visits().unwrap().get("home").map(|v| v.count),
+            Some(1)
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn alias_reference() {
+        let home_dir = std::env::var("HOME").unwrap();
+        let mut subcmd = fixture::create_subcmd(Resolve {
+            path: PathBuf::from_str("home").unwrap(),
+            record: false,
+            mkdir: false,
+            no_check: false,
+            ancestor: false,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            root = "~"
+            home = "@root/"
+        ]);
+        let res = subcmd.run();
+        assert_eq!(res.unwrap(), format!("{}\n", home_dir));
+    }
+
+    #[test]
+    #[serial]
+    fn alias_cycle() {
+        let mut subcmd = fixture::create_subcmd(Resolve {
+            path: PathBuf::from_str("a/sub").unwrap(),
+            record: false,
+            mkdir: false,
+            no_check: false,
+            ancestor: false,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            a = "@b"
+            b = "@a"
+        ]);
+        let res = subcmd.run();
+        assert_eq!(
+            format!("{:#}", res.unwrap_err()),
+            "failed to resolve alias for path 'a/sub': cyclic alias references: a -> b -> a"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn prefix_alias() {
+        let home_dir = std::env::var("HOME").unwrap();
+        let mut subcmd = fixture::create_subcmd(Resolve {
+            path: PathBuf::from_str("hom").unwrap(),
+            record: false,
+            mkdir: false,
+            no_check: false,
+            ancestor: false,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            home = "~"
+            work = "~"
+        ]);
+        std::env::set_var("_RAD_MATCH", "prefix");
+        let res = subcmd.run();
+        std::env::remove_var("_RAD_MATCH");
+        assert_eq!(res.unwrap(), format!("{}\n", home_dir));
+    }
+
+    #[test]
+    #[serial]
+    fn relative_dir_wins_over_prefix() {
+        let mut subcmd = fixture::create_subcmd(Resolve {
+            path: PathBuf::from_str("src/missing").unwrap(),
+            record: false,
+            mkdir: false,
+            no_check: true,
+            ancestor: false,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            sources = "~"
+        ]);
+        std::env::set_var("_RAD_MATCH", "prefix");
+        let res = subcmd.run();
+        std::env::remove_var("_RAD_MATCH");
+        let expected = std::env::current_dir().unwrap().join("src/missing");
+        assert_eq!(res.unwrap(), format!("{}\n", expected.display()));
+    }
+
+    #[test]
+    #[serial]
+    fn ambiguous_alias() {
+        let mut subcmd = fixture::create_subcmd(Resolve {
+            path: PathBuf::from_str("ho").unwrap(),
+            record: false,
+            mkdir: false,
+            no_check: false,
+            ancestor: false,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            home = "~"
+            host = "~"
+        ]);
+        std::env::set_var("_RAD_MATCH", "prefix");
+        let res = subcmd.run();
+        std::env::remove_var("_RAD_MATCH");
+        assert_eq!(
+            format!("{:#}", res.unwrap_err()),
+            "failed to resolve alias for path 'ho': alias 'ho' is ambiguous, candidates are: home, host"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn unset_variable() {
+        let mut subcmd = fixture::create_subcmd(Resolve {
+            path: PathBuf::from_str("api").unwrap(),
+            record: false,
+            mkdir: false,
+            no_check: false,
+            ancestor: false,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            api = "$RUALDI_TEST_UNSET/api"
+        ]);
+        let res = subcmd.run();
+        assert_eq!(
+            format!("{:#}", res.unwrap_err()),
+            "failed to resolve alias for path 'api': environment variable 'RUALDI_TEST_UNSET' used by alias 'api' is not set: environment variable not found"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn mkdir() {
+        let mut subcmd = fixture::create_subcmd(Resolve {
+            path: PathBuf::from_str("tmp/new/thing").unwrap(),
+            record: false,
+            mkdir: true,
+            no_check: false,
+            ancestor: false,
+        });
+        let tmp_dir = subcmd.tmp.tmp_dir.path().to_path_buf();
+        subcmd.use_config(
+            toml::from_str(&format!("[aliases]\ntmp = {:?}", tmp_dir.to_str().unwrap())).unwrap(),
+        );
+        let res = subcmd.run();
+        let expected = tmp_dir.join("new/thing");
+        assert_eq!(res.unwrap(), format!("{}\n", expected.display()));
+        assert!(expected.is_dir());
+    }
+
+    #[test]
+    #[serial]
+    fn mkdir_outside_alias() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        for path in ["typo", "tmp/../outside"] {
+            let mut subcmd = fixture::create_subcmd(Resolve {
+                path: PathBuf::from_str(path).unwrap(),
+                record: false,
+                mkdir: true,
+                no_check: false,
+                ancestor: false,
+            });
+            subcmd.use_config(
+                toml::from_str(&format!(
+                    "[aliases]\ntmp = {:?}",
+                    tmp_dir.path().join("aliased").to_str().unwrap()
+                ))
+                .unwrap(),
+            );
+            let res = subcmd.run();
+            assert_eq!(
+                format!("{:#}", res.unwrap_err()),
+                format!(
+                    "failed to resolve alias for path '{}': \
+                     --mkdir only creates directories below the path of an alias",
+                    path
+                )
+            );
+        }
+        assert!(!Path::new("typo").exists());
+        assert!(!tmp_dir.path().join("outside").exists());
+    }
+
+    #[test]
+    #[serial]
+    fn no_check() {
+        let current_dir = std::env::current_dir().unwrap();
+        let mut subcmd = fixture::create_subcmd(Resolve {
+            path: PathBuf::from_str("test/../other").unwrap(),
+            record: false,
+            mkdir: false,
+            no_check: true,
+            ancestor: false,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            test = "not-existing-path"
+        ]);
+        let res = subcmd.run();
+        assert_eq!(
+            res.unwrap(),
+            format!("{}/other\n", current_dir.to_str().unwrap())
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn ancestor() {
+        let mut subcmd = fixture::create_subcmd(Resolve {
+            path: PathBuf::from_str("tmp/not/existing").unwrap(),
+            record: false,
+            mkdir: false,
+            no_check: false,
+            ancestor: true,
+        });
+        let tmp_dir = subcmd.tmp.tmp_dir.path().to_path_buf();
+        subcmd.use_config(
+            toml::from_str(&format!("[aliases]\ntmp = {:?}", tmp_dir.to_str().unwrap())).unwrap(),
+        );
+        let res = subcmd.run();
+        assert_eq!(res.unwrap(), format!("{}\n", tmp_dir.display()));
+        assert!(!tmp_dir.join("not").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[serial]
+    fn physical_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = std::fs::canonicalize(dir.path()).unwrap();
+        std::fs::create_dir_all(base.join("real/target")).unwrap();
+        std::os::unix::fs::symlink(base.join("real/target"), base.join("link")).unwrap();
+        let path = base.join("link/..");
+
+        let subcmd = fixture::create_subcmd(Resolve {
+            path: path.clone(),
+            record: false,
+            mkdir: false,
+            no_check: false,
+            ancestor: false,
+        });
+        std::env::remove_var("_RAD_RESOLVE_SYMLINKS");
+        assert_eq!(subcmd.run().unwrap(), format!("{}\n", base.display()));
+
+        std::env::set_var("_RAD_RESOLVE_SYMLINKS", "1");
+        let res = subcmd.run();
+        std::env::remove_var("_RAD_RESOLVE_SYMLINKS");
+        assert_eq!(res.unwrap(), format!("{}\n", base.join("real").display()));
+    }
 }
@@ -1,6 +1,7 @@
 use crate::config;
 #[cfg(test)]
 use crate::fixture;
+use crate::subcommand::env::checked_env_name;
 use crate::subcommand::RadSubCmdRunnable;
 use anyhow::{Context, Result};
 use rualdlib::Aliases;
@@ -8,11 +9,14 @@ use rualdlib::Aliases;
 use serial_test::serial;
 use structopt::StructOpt;
 
-/// Resolve enironment variable from alias
+/// Resolve enironment variables from alias, one per line
 #[derive(Debug, StructOpt)]
 pub struct ResolveEnv {
-    /// Alias for which to find environment variable
+    /// Alias for which to find environment variables
     pub alias: String,
+    /// Print the names variables are exported as, with prefix and case
+    #[structopt(long)]
+    pub exported: bool,
 }
 
 impl RadSubCmdRunnable for ResolveEnv {
@@ -23,20 +27,35 @@ impl RadSubCmdRunnable for ResolveEnv {
                 self.alias
             )
         })?;
-        let aliases = Aliases::open(aliases_dir).with_context(|| {
+        let mut aliases = Aliases::open(aliases_dir).with_context(|| {
             format!(
                 "fail to resolve environment variable for alias '{}'",
                 self.alias
             )
         })?;
+        config::set_env_naming(&mut aliases)?;
 
-        let resolved_var = aliases.get_env(&self.alias).with_context(|| {
+        let resolved_vars = aliases.get_env(&self.alias).with_context(|| {
             format!(
                 "fail to resolve environment variable for alias '{}'",
                 self.alias
             )
         })?;
-        Ok(format!("{}\n", resolved_var))
+
+        let mut res = String::new();
+        for var in resolved_vars {
+            if self.exported {
+                let prefixed = aliases.get_env_entry(&var).is_none_or(|e| e.prefixed);
+                match checked_env_name(&aliases, &var, prefixed) {
+                    Some(name) => res.push_str(&name),
+                    None => continue,
+                }
+            } else {
+                res.push_str(&var);
+            }
+            res.push('\n');
+        }
+        Ok(res)
     }
 }
 
@@ -49,6 +68,7 @@ mod tests {
     fn existing_var() {
         let mut subcmd = fixture::create_subcmd(ResolveEnv {
             alias: String::from("test"),
+            exported: false,
         });
         subcmd.use_config(toml::toml![
             [aliases]
@@ -61,11 +81,48 @@ mod tests {
         assert_eq!(res.unwrap(), "TEST\n");
     }
 
+    #[test]
+    #[serial]
+    fn exported_vars() {
+        let mut subcmd = fixture::create_subcmd(ResolveEnv {
+            alias: String::from("test"),
+            exported: true,
+        });
+        subcmd.use_config(toml::toml![
+            version = 6
+            [aliases]
+            test = "test"
+            [environment]
+            TEST = "test"
+            TEST_ROOT = { alias = "test", prefix = false }
+        ]);
+        assert_eq!(subcmd.run().unwrap(), "RAD_TEST\nTEST_ROOT\n");
+    }
+
+    #[test]
+    #[serial]
+    fn exported_invalid_names() {
+        let mut subcmd = fixture::create_subcmd(ResolveEnv {
+            alias: String::from("test"),
+            exported: true,
+        });
+        subcmd.use_config(toml::toml![
+            version = 6
+            [aliases]
+            test = "test"
+            [environment]
+            TEST = "test"
+            PATH = { alias = "test", prefix = false }
+        ]);
+        assert_eq!(subcmd.run().unwrap(), "RAD_TEST\n");
+    }
+
     #[test]
     #[serial]
     fn not_existing_var() {
         let mut subcmd = fixture::create_subcmd(ResolveEnv {
             alias: String::from("test"),
+            exported: false,
         });
         subcmd.use_config(toml::toml![
             [aliases]
@@ -0,0 +1,121 @@
+use crate::{config, subcommand::RadSubCmdRunnable, utils};
+
+#[cfg(test)]
+use crate::fixture;
+use anyhow::{Context, Result};
+use colored::*;
+use rualdlib::{AliasEntry, Aliases};
+#[cfg(test)]
+use serial_test::serial;
+use std::path::PathBuf;
+#[cfg(test)]
+use std::str::FromStr;
+use structopt::StructOpt;
+
+/// Point an existing alias on a new path
+#[derive(Debug, StructOpt)]
+pub struct Set {
+    /// Alias to update
+    pub alias: String,
+    /// New path of the alias, if not provided current directory is used.
+    /// A path starting with `@alias` is relative to another alias
+    #[structopt(parse(from_os_str))]
+    pub path: Option<PathBuf>,
+}
+
+impl RadSubCmdRunnable for Set {
+    fn run(&self) -> Result<String> {
+        let context = || {
+            format!(
+                "[{}] Failed to update: {}",
+                ctype_exp!("alias"),
+                self.alias.red().bold()
+            )
+        };
+        let aliases_dir = config::rad_aliases_dir().with_context(context)?;
+        let mut aliases = Aliases::open(aliases_dir).with_context(context)?;
+
+        let path = self.path.to_owned().unwrap_or(utils::get_current_dir()?);
+        let path = if utils::is_alias_reference(&path) {
+            path
+        } else {
+            utils::resolve_path(path, config::rad_path_mode()).with_context(context)?
+        };
+        let old_path = aliases
+            .update_entry(self.alias.to_owned(), AliasEntry::from_path(&path))
+            .with_context(context)?;
+
+        Ok(format!(
+            "[{}] Updated: {} {} {} {}\n",
+            ctype_exp!("alias"),
+            self.alias.green().bold(),
+            old_path.red(),
+            "=>".bright_cyan(),
+            path.display().to_string().green()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[serial]
+    fn not_existing_alias() {
+        let subcmd = fixture::create_subcmd(Set {
+            alias: String::from("test"),
+            path: None,
+        });
+        let res = subcmd.run();
+        assert!(res.is_err());
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "[alias] Failed to update: test"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn not_existing_path() {
+        let mut subcmd = fixture::create_subcmd(Set {
+            alias: String::from("test"),
+            path: Some(PathBuf::from_str("not-existing-path").unwrap()),
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            test = "test"
+        ]);
+        let res = subcmd.run();
+        assert!(res.is_err());
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "[alias] Failed to update: test"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn existing_alias() {
+        let mut subcmd = fixture::create_subcmd(Set {
+            alias: String::from("test"),
+            path: None,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            test = "test"
+            [environment]
+            test = "TEST"
+        ]);
+        let res = subcmd.run();
+        assert!(res.is_ok());
+        let current_dir = utils::get_current_dir().unwrap();
+        assert_eq!(
+            res.unwrap(),
+            format!("[alias] Updated: test test => {}\n", current_dir.display())
+        );
+
+        let aliases = Aliases::open(subcmd.tmp.tmp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(aliases.get_env("test").unwrap(), vec!["TEST"]);
+    }
+}
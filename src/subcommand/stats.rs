@@ -0,0 +1,53 @@
+use crate::config;
+#[cfg(test)]
+use crate::fixture;
+use crate::subcommand::RadSubCmdRunnable;
+use anyhow::{Context, Result};
+use rualdlib::Aliases;
+#[cfg(test)]
+use serial_test::serial;
+use structopt::StructOpt;
+
+/// Print how often and how recently aliases were jumped to
+#[derive(Debug, StructOpt)]
+pub struct Stats {}
+
+impl RadSubCmdRunnable for Stats {
+    fn run(&self) -> Result<String> {
+        let aliases_dir = config::rad_aliases_dir().with_context(|| "fail to list statistics")?;
+        let aliases = Aliases::open(aliases_dir).with_context(|| "fail to list statistics")?;
+
+        let res = aliases
+            .stats()
+            .unwrap_or_else(|| "No aliases found\n".into());
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[serial]
+    fn no_aliases() {
+        let subcmd = fixture::create_subcmd(Stats {});
+        let res = subcmd.run();
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), "No aliases found\n");
+    }
+
+    #[test]
+    #[serial]
+    fn not_visited() {
+        let mut subcmd = fixture::create_subcmd(Stats {});
+        subcmd.use_config(toml::toml![
+            [aliases]
+            test = "test"
+        ]);
+        let res = subcmd.run();
+        assert!(res.is_ok());
+        assert!(res.unwrap().ends_with("\ntest         =>     0 visits\n"));
+    }
+}
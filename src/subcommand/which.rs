@@ -0,0 +1,138 @@
+use crate::config;
+#[cfg(test)]
+use crate::fixture;
+use crate::subcommand::RadSubCmdRunnable;
+use crate::utils;
+use anyhow::{bail, Context, Result};
+use rualdlib::Aliases;
+#[cfg(test)]
+use serial_test::serial;
+use std::path::PathBuf;
+#[cfg(test)]
+use std::str::FromStr;
+use structopt::StructOpt;
+
+/// Print the alias covering a path followed by the rest of the path
+#[derive(Debug, StructOpt)]
+pub struct Which {
+    /// Path to search aliases for, if not provided current directory is used
+    #[structopt(parse(from_os_str))]
+    pub path: Option<PathBuf>,
+    /// Print every alias covering the path, longest alias path first
+    #[structopt(short, long)]
+    pub all: bool,
+}
+
+impl RadSubCmdRunnable for Which {
+    fn run(&self) -> Result<String> {
+        let aliases_dir = config::rad_aliases_dir().with_context(|| "fail to search aliases")?;
+        let mut aliases = Aliases::open(aliases_dir).with_context(|| "fail to search aliases")?;
+
+        let mode = config::rad_path_mode();
+        let path = match &self.path {
+            Some(path) => path.to_owned(),
+            None => utils::get_working_dir(mode)?,
+        };
+        let path = utils::normalize_path(path, mode)?;
+        aliases.set_physical_paths(mode == utils::PathMode::Physical);
+
+        let matches = aliases.which(&path);
+        if matches.is_empty() {
+            bail!("no alias covers path: {}", path.display());
+        }
+        let count = if self.all { matches.len() } else { 1 };
+        let res = matches
+            .iter()
+            .take(count)
+            .map(|(alias, suffix)| {
+                if suffix.as_os_str().is_empty() {
+                    format!("{}\n", alias)
+                } else {
+                    format!("{}/{}\n", alias, suffix.display())
+                }
+            })
+            .collect();
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[serial]
+    fn no_alias() {
+        let subcmd = fixture::create_subcmd(Which {
+            path: Some(PathBuf::from_str("/not/aliased").unwrap()),
+            all: false,
+        });
+        let res = subcmd.run();
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "no alias covers path: /not/aliased"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn longest_alias() {
+        let mut subcmd = fixture::create_subcmd(Which {
+            path: Some(PathBuf::from_str("/src/api/src/../src/handlers").unwrap()),
+            all: false,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            src = "/src"
+            api = "@src/api"
+        ]);
+        let res = subcmd.run();
+        assert_eq!(res.unwrap(), "api/src/handlers\n");
+    }
+
+    #[test]
+    #[serial]
+    fn all() {
+        let mut subcmd = fixture::create_subcmd(Which {
+            path: Some(PathBuf::from_str("/src/api").unwrap()),
+            all: true,
+        });
+        subcmd.use_config(toml::toml![
+            [aliases]
+            src = "/src"
+            api = "@src/api"
+        ]);
+        let res = subcmd.run();
+        assert_eq!(res.unwrap(), "api\nsrc/api\n");
+    }
+
+    #[test]
+    #[serial]
+    fn symlinked_current_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = std::fs::canonicalize(dir.path()).unwrap();
+        std::fs::create_dir_all(base.join("real/proj")).unwrap();
+        std::os::unix::fs::symlink(base.join("real"), base.join("link")).unwrap();
+        let proj = base.join("link/proj");
+
+        let mut subcmd = fixture::create_subcmd(Which {
+            path: None,
+            all: false,
+        });
+        subcmd.use_config(
+            toml::from_str(&format!("[aliases]\nproj = {:?}", proj.to_str().unwrap())).unwrap(),
+        );
+        let current_dir = std::env::current_dir().unwrap();
+        let pwd = std::env::var_os("PWD");
+        std::env::set_current_dir(&proj).unwrap();
+        std::env::set_var("PWD", &proj);
+        let res = subcmd.run();
+        std::env::set_current_dir(current_dir).unwrap();
+        match pwd {
+            Some(pwd) => std::env::set_var("PWD", pwd),
+            None => std::env::remove_var("PWD"),
+        }
+        assert_eq!(res.unwrap(), "proj\n");
+    }
+}
@@ -1,8 +1,40 @@
 use anyhow::{bail, Context, Result};
-use std::env;
+use std::io::{self, Write};
 use std::path::{Component, Path, PathBuf};
+use std::{env, fs};
 
-pub fn resolve_path<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+/// How symlinks are handled when resolving a path
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathMode {
+    /// Symlinks are kept and `..` removes the previous component, as `pwd -L`
+    Logical,
+    /// Symlinks are replaced by their target before applying `..`, as `pwd -P`
+    Physical,
+}
+
+/// Make path absolute and remove `.` and `..` components
+/// without checking it exists
+pub fn normalize_path<P: AsRef<Path>>(path: P, mode: PathMode) -> Result<PathBuf> {
+    match mode {
+        PathMode::Logical => lexical_path(path),
+        PathMode::Physical => physical_path(get_current_dir()?.join(path)),
+    }
+}
+
+/// Replace symlinks of the existing part of path by their target
+/// before removing `..` components of the missing part
+fn physical_path(path: PathBuf) -> Result<PathBuf> {
+    let existing = path
+        .ancestors()
+        .find(|ancestor| ancestor.exists())
+        .unwrap_or_else(|| Path::new("/"));
+    let target = fs::canonicalize(existing)
+        .with_context(|| format!("could not resolve path: {}", path.display()))?;
+    let missing = path.strip_prefix(existing).unwrap();
+    lexical_path(target.join(missing))
+}
+
+fn lexical_path<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
     let path = path.as_ref();
     let base_path;
 
@@ -16,7 +48,7 @@ pub fn resolve_path<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
             stack.push(root);
         }
         _ => {
-            base_path = get_current_dir()?;
+            base_path = get_working_dir(PathMode::Logical)?;
             stack.extend(base_path.components());
         }
     }
@@ -34,19 +66,90 @@ pub fn resolve_path<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
         }
     }
 
-    let result = stack.iter().collect::<PathBuf>();
+    Ok(stack.iter().collect::<PathBuf>())
+}
+
+pub fn resolve_path<P: AsRef<Path>>(path: P, mode: PathMode) -> Result<PathBuf> {
+    let result = normalize_path(path, mode)?;
     if !result.is_dir() {
         bail!("could not resolve path: {}", result.display());
     }
     Ok(result)
 }
 
+/// Resolve path, creating the missing directories
+pub fn create_path<P: AsRef<Path>>(path: P, mode: PathMode) -> Result<PathBuf> {
+    let result = normalize_path(path, mode)?;
+    fs::create_dir_all(&result)
+        .with_context(|| format!("could not create directory: {}", result.display()))?;
+    Ok(result)
+}
+
+/// Resolve path to its deepest existing ancestor directory
+pub fn resolve_ancestor<P: AsRef<Path>>(path: P, mode: PathMode) -> Result<PathBuf> {
+    let result = normalize_path(path, mode)?;
+    result
+        .ancestors()
+        .find(|ancestor| ancestor.is_dir())
+        .map(Path::to_path_buf)
+        .with_context(|| format!("could not resolve path: {}", result.display()))
+}
+
+/// Whether path is a reference to another alias such as `@alias/sub`,
+/// stored as is instead of being resolved
+pub fn is_alias_reference<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref()
+        .to_str()
+        .is_some_and(|path| path.starts_with('@'))
+}
+
 pub fn get_current_dir() -> Result<PathBuf> {
     env::current_dir().context("could not get current path")
 }
 
-pub fn path_to_str<P: AsRef<Path>>(path: &P) -> Result<&str> {
-    let path = path.as_ref();
-    path.to_str()
-        .with_context(|| format!("invalid utf-8 sequence in path: {}", path.display()))
+/// Get the current directory as the shell shows it: in logical mode `$PWD`
+/// keeps the symlinks followed by `cd`, as long as it is absolute and still
+/// names the current directory, as `pwd -L` does
+pub fn get_working_dir(mode: PathMode) -> Result<PathBuf> {
+    let current_dir = get_current_dir()?;
+    if mode == PathMode::Physical {
+        return Ok(current_dir);
+    }
+    let pwd = match env::var_os("PWD").map(PathBuf::from) {
+        Some(pwd) if pwd.is_absolute() => pwd,
+        _ => return Ok(current_dir),
+    };
+    let same_dir = pwd
+        .components()
+        .all(|c| matches!(c, Component::RootDir | Component::Normal(_)))
+        && fs::canonicalize(&pwd).ok() == fs::canonicalize(&current_dir).ok();
+    Ok(if same_dir { pwd } else { current_dir })
+}
+
+/// Get the raw bytes of path, non unicode parts are replaced
+/// on platforms without byte paths
+pub fn path_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        path.as_ref().as_os_str().as_bytes().to_vec()
+    }
+    #[cfg(not(unix))]
+    {
+        path.as_ref().to_string_lossy().into_owned().into_bytes()
+    }
+}
+
+/// Print path followed by a newline as raw bytes, so that paths which
+/// are not valid UTF-8 can be given to `cd`
+pub fn print_path<P: AsRef<Path>>(path: P) -> Result<String> {
+    let mut bytes = path_bytes(path);
+    bytes.push(b'\n');
+
+    let mut stdout = io::stdout();
+    stdout
+        .write_all(&bytes)
+        .and_then(|_| stdout.flush())
+        .context("could not print path")?;
+    Ok(String::new())
 }
@@ -0,0 +1,15 @@
+use crate::common;
+use anyhow::Result;
+
+#[test]
+fn alias() -> Result<()> {
+    let mut rad = common::create_rad("abbrev");
+    rad.use_config(toml::toml![
+        [aliases]
+        api = "/src/api"
+    ]);
+    let output = rad.cmd.arg("/src/api/handlers").output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(actual, "@api/handlers\n");
+    Ok(())
+}
@@ -49,3 +49,23 @@ Caused by:
     );
     Ok(())
 }
+
+#[cfg(unix)]
+#[test]
+fn not_unicode_path() -> Result<()> {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+    let mut rad = common::create_rad("add");
+    let path = rad.tmp.tmp_dir.path().join(OsStr::from_bytes(b"caf\xe9"));
+    std::fs::create_dir(&path)?;
+    let output = rad.cmd.arg("legacy").arg(&path).output()?;
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "[alias] Added: legacy\n"
+    );
+    let content = std::fs::read_to_string(rad.tmp.tmp_dir.path().join("rualdi.toml"))?;
+    assert!(content.contains(&format!(
+        "legacy = {{ path = '{}/caf\\xe9', escaped = true }}",
+        rad.tmp.tmp_dir.path().to_str().unwrap()
+    )));
+    Ok(())
+}
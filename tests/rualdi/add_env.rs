@@ -68,7 +68,7 @@ fn existing_var() -> Result<()> {
         r#"Error: Failed to add: [env] TEST for [alias] test
 
 Caused by:
-    alias 'test' has already a environment variable assiociated
+    environment variable 'TEST' for alias 'test' already exists
 "#
     );
     Ok(())
@@ -0,0 +1,26 @@
+use crate::common;
+use anyhow::Result;
+
+#[test]
+fn exports() -> Result<()> {
+    let mut rad = common::create_rad("env");
+    let dir = rad.tmp.tmp_dir.path().to_path_buf();
+    rad.use_config(toml::from_str(&format!(
+        r#"
+        [aliases]
+        config = "{}"
+        gone = "/not/existing/path"
+        [environment]
+        config = "config"
+        gone = "gone"
+        "#,
+        dir.display()
+    ))?);
+    let output = rad.cmd.args(["--shell", "fish"]).output()?;
+    assert!(output.status.success());
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(actual, format!("set -gx RAD_CONFIG '{}'\n", dir.display()));
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert!(error.contains("Alias gone is broken, RAD_GONE not exported"));
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use crate::common;
+use anyhow::Result;
+use std::fs;
+
+#[test]
+fn writes_file() -> Result<()> {
+    let mut rad = common::create_rad("export-env");
+    let dir = rad.tmp.tmp_dir.path().to_path_buf();
+    let output = dir.join("environment.d/rualdi.conf");
+    rad.use_config(toml::from_str(&format!(
+        r#"
+        version = 6
+        [aliases]
+        config = "{}"
+        [environment]
+        CONFIG = "config"
+        "#,
+        dir.display()
+    ))?);
+    let status = rad
+        .cmd
+        .args(["--format", "systemd", "--all", "--output"])
+        .arg(&output)
+        .status()?;
+    assert!(status.success());
+    let content = fs::read_to_string(&output)?;
+    assert!(content.starts_with('#'));
+    assert!(content.ends_with(&format!("\nRAD_CONFIG={}\n", dir.display())));
+    Ok(())
+}
@@ -22,3 +22,25 @@ fn filled() -> Result<()> {
     assert_eq!(actual, "=\nALIASES\n=\ntest         => test\n");
     Ok(())
 }
+
+#[test]
+fn host_section() -> Result<()> {
+    let mut rad = common::create_rad("list");
+    rad.use_config(toml::toml![
+        [aliases]
+        test = "test"
+
+        [hosts.work]
+        env = { WORK = "1" }
+
+        [hosts.work.aliases]
+        vpn = "vpn"
+    ]);
+    let output = rad.cmd.env("WORK", "1").output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        actual,
+        "=\nALIASES\n=\ntest         => test\nvpn          => vpn [host: work]\n"
+    );
+    Ok(())
+}
@@ -1,9 +1,16 @@
+mod abbrev;
 mod add;
 mod add_env;
 mod common;
+mod env;
+mod export_env;
 mod list;
 mod list_env;
 mod remove;
 mod remove_env;
+mod rename;
 mod resolve;
 mod resolve_env;
+mod set;
+mod stats;
+mod which;
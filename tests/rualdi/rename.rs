@@ -0,0 +1,36 @@
+use crate::common;
+use anyhow::Result;
+
+#[test]
+fn not_existing_alias() -> Result<()> {
+    let mut rad = common::create_rad("rename");
+    let output = rad.cmd.arg("test").arg("renamed").output()?;
+    let actual = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        actual,
+        r#"Error: [alias] Failed to rename: test
+
+Caused by:
+    alias 'test' not exists
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn existing_alias_with_env() -> Result<()> {
+    let mut rad = common::create_rad("rename");
+    rad.use_config(toml::toml![
+        [aliases]
+        test = "test"
+        [environment]
+        test = "TEST"
+    ]);
+    let output = rad.cmd.arg("test").arg("renamed").output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        actual,
+        "[alias] Renamed: test to renamed\n[env] Moved: TEST to [alias] renamed\n"
+    );
+    Ok(())
+}
@@ -56,3 +56,61 @@ fn tild_alias() -> Result<()> {
     assert_eq!(actual, expected);
     Ok(())
 }
+
+#[test]
+fn ancestor() -> Result<()> {
+    let current_dir = std::env::current_dir().unwrap();
+    let mut rad = common::create_rad("resolve");
+    rad.use_config(toml::toml![
+        [aliases]
+        test = "not-existing-path"
+    ]);
+    let output = rad.cmd.args(["--ancestor", "test/sub"]).output()?;
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("{}\n", current_dir.to_str().unwrap())
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        format!(
+            "[alias] Path {}/not-existing-path/sub does not exist, falling back to {}\n",
+            current_dir.to_str().unwrap(),
+            current_dir.to_str().unwrap()
+        )
+    );
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn not_unicode_path() -> Result<()> {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+    let mut rad = common::create_rad("resolve");
+    let base = rad.tmp.tmp_dir.path().to_path_buf();
+    std::fs::create_dir_all(base.join(OsStr::from_bytes(b"caf\xe9/sub")))?;
+    rad.use_config(
+        toml::from_str(&format!(
+            "[aliases]\nlegacy = {{ path = {:?}, escaped = true }}",
+            format!("{}/caf\\xe9", base.to_str().unwrap())
+        ))
+        .unwrap(),
+    );
+    let output = rad.cmd.arg("legacy/sub").output()?;
+    let mut expected = base.to_str().unwrap().as_bytes().to_vec();
+    expected.extend_from_slice(b"/caf\xe9/sub\n");
+    assert_eq!(output.stdout, expected);
+    Ok(())
+}
+
+#[test]
+fn candidates() -> Result<()> {
+    let mut rad = common::create_rad("resolve");
+    rad.use_config(toml::toml![
+        [aliases]
+        test = ["/not-existing-path", "/"]
+    ]);
+    let output = rad.cmd.arg("test").output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(actual, "/\n");
+    Ok(())
+}
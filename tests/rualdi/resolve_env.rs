@@ -30,7 +30,7 @@ fn not_existing_var() -> Result<()> {
         r#"Error: fail to resolve environment variable for alias 'test'
 
 Caused by:
-    no variable for alias 'test'
+    no such environment variable for alias 'test'
 "#,
     );
     Ok(())
@@ -0,0 +1,11 @@
+use crate::common;
+use anyhow::Result;
+
+#[test]
+fn no_aliases() -> Result<()> {
+    let mut rad = common::create_rad("stats");
+    let output = rad.cmd.output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(actual, "No aliases found\n");
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use crate::common;
+use anyhow::Result;
+
+#[test]
+fn current_dir() -> Result<()> {
+    let current_dir = std::env::current_dir().unwrap();
+    let mut rad = common::create_rad("which");
+    rad.use_config(toml::toml![
+        [aliases]
+        root = "/"
+    ]);
+    let output = rad.cmd.output()?;
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        actual,
+        format!("root/{}\n", current_dir.strip_prefix("/")?.display())
+    );
+    Ok(())
+}
+
+#[test]
+fn no_alias() -> Result<()> {
+    let mut rad = common::create_rad("which");
+    let output = rad.cmd.arg("/not/aliased").output()?;
+    let actual = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(actual, "Error: no alias covers path: /not/aliased\n");
+    Ok(())
+}